@@ -0,0 +1,115 @@
+use crate::component::toast::use_toast;
+use crate::util::common;
+use crate::util::credential;
+use crate::util::error::ToError;
+use crate::util::form::{self, use_form, Field, InputField};
+use crate::util::i18n::{self, use_translation};
+use crate::util::password::{self, PasswordStrength};
+use crate::util::request::{self, Host};
+use serde::Serialize;
+use std::rc::Rc;
+use wasm_bindgen_futures::spawn_local;
+use yew::prelude::*;
+
+#[derive(Serialize)]
+struct ChangePwdReq {
+    old_pwd: String,
+    pwd: String,
+}
+
+// the server signs out every other session of the user and returns
+// a new token for this one
+async fn change_pwd(req: ChangePwdReq) -> common::BasicResult<()> {
+    let res = request::put::<String, _>(Host::AxumBase, "/user/change_pwd", &req).await?;
+    if let Some(token) = res.data {
+        common::set_local_storage(crate::util::TOKEN_KEY, &token);
+    }
+    Ok(())
+}
+
+// the new password must not be the current one
+fn not_old_pwd() -> form::Validator {
+    Rc::new(|value, values| {
+        match values
+            .get("old_pwd")
+            .is_some_and(|x| !x.is_empty() && x == value)
+        {
+            true => Err(i18n::t("change_pwd.same_as_old").to_validation_error()),
+            false => Ok(()),
+        }
+    })
+}
+
+#[function_component(ChangePwd)]
+pub fn change_pwd_page() -> Html {
+    let i18n = use_translation();
+    let toaster = use_toast();
+    let form = use_form(|| {
+        vec![
+            Field::new("old_pwd").validate(form::required()),
+            Field::new("pwd")
+                .validate(form::pwd())
+                .validate(not_old_pwd()),
+            Field::new("pwd_confirm").validate(form::pwd_confirm("pwd")),
+        ]
+    });
+    password::use_policy_revalidation(&form, &["pwd", "pwd_confirm"]);
+    let submitting = use_state(|| false);
+
+    let on_submit = {
+        let form = form.clone();
+        let toaster = toaster.clone();
+        let submitting = submitting.clone();
+        Callback::from(move |_| {
+            if *submitting || !form.touch_all() {
+                return;
+            }
+            let pwd = form.value("pwd");
+            let req = ChangePwdReq {
+                old_pwd: form.value("old_pwd"),
+                pwd: pwd.clone(),
+            };
+            submitting.set(true);
+            let form = form.clone();
+            let toaster = toaster.clone();
+            let submitting = submitting.clone();
+            spawn_local(async move {
+                match change_pwd(req).await {
+                    Ok(_) => {
+                        form.reset();
+                        toaster.ok(&i18n::t("change_pwd.success"));
+                        if let Ok(user) = common::get_current_user() {
+                            credential::store(&user.email, &pwd).await;
+                        }
+                    }
+                    Err(err) => toaster.error(&err.to_string()),
+                }
+                submitting.set(false);
+            });
+        })
+    };
+
+    html! {
+        <div class="columns is-centered">
+            <div class="column is-half">
+                <div class="box">
+                    <p class="title is-4">{i18n.t("change_pwd.title")}</p>
+                    <p class="subtitle is-6">{i18n.t("change_pwd.hint")}</p>
+                    <InputField form={form.clone()} name="old_pwd" label={i18n.t("change_pwd.old_pwd")} input_type="password"
+                        placeholder="**********" autocomplete="current-password" icon="fas fa-lock" idle="" autofocus=true/>
+                    <InputField form={form.clone()} name="pwd" label={i18n.t("change_pwd.pwd")} input_type="password"
+                        placeholder="**********" autocomplete="new-password" icon="fas fa-key" idle=""/>
+                    <PasswordStrength value={form.value("pwd")}/>
+                    <InputField form={form.clone()} name="pwd_confirm" label={i18n.t("change_pwd.pwd_confirm")} input_type="password"
+                        placeholder="**********" autocomplete="new-password" icon="fas fa-key" idle="" onenter={on_submit.clone()}/>
+                    <div class="field">
+                        <button class={classes!("button", "is-primary", submitting.then_some("is-loading"))}
+                            disabled={!form.can_submit() || *submitting} onclick={on_submit.reform(|_| ())}>
+                            {i18n.t("change_pwd.submit")}
+                        </button>
+                    </div>
+                </div>
+            </div>
+        </div>
+    }
+}
@@ -0,0 +1,121 @@
+use crate::util::common::BasicResult;
+use crate::util::error::ToError;
+use crate::util::request::{self, Host};
+use crate::util::sanitize;
+use serde::{Deserialize, Serialize};
+use web_sys::{File, Url};
+use yew::Callback;
+
+pub const MAX_ATTACHMENT_SIZE: f64 = 10.0 * 1024.0 * 1024.0;
+pub const MAX_ATTACHMENTS: usize = 5;
+const ALLOWED_TYPES: [&str; 6] = [
+    "image/",
+    "text/plain",
+    "application/pdf",
+    "application/zip",
+    "application/msword",
+    "application/vnd.openxmlformats-officedocument.",
+];
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Default)]
+pub struct Attachment {
+    // empty until the file is uploaded
+    #[serde(default)]
+    pub url: String,
+    #[serde(default)]
+    pub thumbnail_url: Option<String>,
+    pub name: String,
+    pub mime: String,
+    pub size: u64,
+    // object url of the local file, shown while uploading
+    #[serde(skip)]
+    pub preview: Option<String>,
+    #[serde(skip)]
+    pub progress: Option<u32>,
+    #[serde(skip)]
+    pub file: Option<File>,
+}
+
+// the urls come from the message of a peer, e.g. "javascript:" must not end up
+// in a link. the rules of rendered html plus the object urls of local previews
+fn is_safe_url(url: &str) -> bool {
+    sanitize::is_safe_url(url) || url.starts_with("blob:")
+}
+
+impl Attachment {
+    pub fn from_file(file: File) -> Self {
+        let mime = file.type_();
+        let preview = if mime.starts_with("image/") {
+            Url::create_object_url_with_blob(&file).ok()
+        } else {
+            None
+        };
+        Self {
+            name: file.name(),
+            size: file.size() as u64,
+            mime,
+            preview,
+            file: Some(file),
+            ..Default::default()
+        }
+    }
+
+    pub fn is_image(&self) -> bool {
+        self.mime.starts_with("image/")
+    }
+
+    pub fn is_uploaded(&self) -> bool {
+        !self.url.is_empty()
+    }
+
+    // the link of the file, None while uploading or when the url is not safe
+    pub fn href(&self) -> Option<&str> {
+        Some(self.url.as_str()).filter(|x| is_safe_url(x))
+    }
+
+    pub fn display_url(&self) -> Option<&str> {
+        self.thumbnail_url
+            .as_deref()
+            .filter(|x| is_safe_url(x))
+            .or(self.href())
+            .or(self.preview.as_deref().filter(|x| is_safe_url(x)))
+    }
+
+    pub fn release_preview(&mut self) {
+        if let Some(preview) = self.preview.take() {
+            Url::revoke_object_url(&preview).unwrap_or_default();
+        }
+    }
+}
+
+pub fn validate(file: &File) -> BasicResult<()> {
+    if file.size() > MAX_ATTACHMENT_SIZE {
+        return Err(format!(
+            "{} is larger than {}",
+            file.name(),
+            format_size(MAX_ATTACHMENT_SIZE as u64)
+        )
+        .to_validation_error());
+    }
+    let mime = file.type_();
+    if !ALLOWED_TYPES.iter().any(|x| mime.starts_with(x)) {
+        return Err(format!("{} is not an allowed file type", file.name()).to_validation_error());
+    }
+    Ok(())
+}
+
+pub fn format_size(size: u64) -> String {
+    match size {
+        0..=1023 => format!("{} B", size),
+        1024..=1048575 => format!("{:.1} KB", size as f64 / 1024.0),
+        _ => format!("{:.1} MB", size as f64 / 1024.0 / 1024.0),
+    }
+}
+
+// the server answers with the stored url and, for images, a thumbnail url
+pub async fn upload(file: &File, onprogress: Callback<u32>) -> BasicResult<Attachment> {
+    let res =
+        request::upload::<Attachment>(Host::ApiBase, "/chat/upload", file, onprogress).await?;
+    res.data
+        .ok_or("upload returned no attachment".to_server_error())
+}
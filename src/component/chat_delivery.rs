@@ -0,0 +1,298 @@
+use super::chat_attachment::{self, Attachment};
+use super::chat_history::{self, Histories};
+use super::message_list::{DeliveryStatus, MessageContent};
+use crate::util::common::BasicResult;
+use crate::util::error::ErrorKind;
+use futures::channel::mpsc::UnboundedSender;
+use gloo::timers::callback::Timeout;
+use gloo_net::websocket::Message;
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::rc::Rc;
+use uuid::Uuid;
+use wasm_bindgen_futures::spawn_local;
+use yew::Callback;
+
+pub const SEND_PRE: &str = "message:";
+// a message without ack after this is marked as failed
+const ACK_TIMEOUT_SECS: u32 = 10;
+
+pub type WsWriter = Rc<RefCell<Option<UnboundedSender<Message>>>>;
+
+#[derive(Serialize)]
+struct OutgoingMessage<'a> {
+    client_id: &'a str,
+    room: &'a str,
+    content: &'a str,
+    attachments: &'a [Attachment],
+}
+
+#[derive(Deserialize, Debug)]
+pub struct Ack {
+    pub client_id: String,
+    pub room: String,
+    pub id: u128,
+    pub time: String,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct Delivered {
+    pub room: String,
+    pub id: u128,
+}
+
+pub fn send_frame(ws_writer: &WsWriter, frame: String) -> BasicResult<()> {
+    match &*ws_writer.borrow() {
+        Some(tx) => tx
+            .unbounded_send(Message::Text(frame))
+            .map_err(|e| ErrorKind::OtherError(e.to_string())),
+        None => Err(ErrorKind::OtherError(String::from(
+            "websocket is not connected",
+        ))),
+    }
+}
+
+fn update_message<R>(
+    histories: &Histories,
+    room: &str,
+    client_id: &str,
+    f: impl FnOnce(&mut MessageContent) -> R,
+) -> Option<R> {
+    let mut borrow = histories.borrow_mut();
+    borrow
+        .get_mut(room)
+        .and_then(|x| {
+            x.messages
+                .iter_mut()
+                .find(|x| x.client_id.as_deref() == Some(client_id))
+        })
+        .map(f)
+}
+
+fn set_status(histories: &Histories, room: &str, client_id: &str, status: DeliveryStatus) -> bool {
+    update_message(histories, room, client_id, |x| x.status = Some(status)).is_some()
+}
+
+fn transmit(
+    histories: &Histories,
+    ws_writer: &WsWriter,
+    room: &str,
+    client_id: &str,
+    content: &str,
+    attachments: &[Attachment],
+    onchanged: Callback<()>,
+) {
+    let frame = serde_json::to_string(&OutgoingMessage {
+        client_id,
+        room,
+        content,
+        attachments,
+    })
+    .map_err(ErrorKind::from)
+    .and_then(|x| send_frame(ws_writer, format!("{SEND_PRE}{x}")));
+
+    if let Err(err) = frame {
+        log::error!("send message error: {}", err);
+        set_status(histories, room, client_id, DeliveryStatus::Failed);
+        onchanged.emit(());
+        return;
+    }
+
+    let histories = histories.clone();
+    let room = room.to_string();
+    let client_id = client_id.to_string();
+    Timeout::new(1000 * ACK_TIMEOUT_SECS, move || {
+        let pending = histories
+            .borrow()
+            .get(&room)
+            .and_then(|x| {
+                x.messages
+                    .iter()
+                    .find(|x| x.client_id.as_deref() == Some(client_id.as_str()))
+                    .map(|x| x.status == Some(DeliveryStatus::Pending))
+            })
+            .unwrap_or_default();
+        if pending {
+            set_status(&histories, &room, &client_id, DeliveryStatus::Failed);
+            onchanged.emit(());
+        }
+    })
+    .forget();
+}
+
+// files are uploaded one by one before the message itself is sent
+async fn upload_attachments(
+    histories: &Histories,
+    room: &str,
+    client_id: &str,
+    onchanged: &Callback<()>,
+) -> bool {
+    let files = update_message(histories, room, client_id, |x| {
+        x.attachments
+            .iter()
+            .enumerate()
+            .filter(|(_, a)| !a.is_uploaded())
+            .filter_map(|(i, a)| a.file.clone().map(|f| (i, f)))
+            .collect::<Vec<_>>()
+    })
+    .unwrap_or_default();
+
+    for (index, file) in files {
+        let onprogress = {
+            let histories = histories.clone();
+            let room = room.to_string();
+            let client_id = client_id.to_string();
+            let onchanged = onchanged.clone();
+            Callback::from(move |progress: u32| {
+                update_message(&histories, &room, &client_id, |x| {
+                    if let Some(a) = x.attachments.get_mut(index) {
+                        a.progress = Some(progress);
+                    }
+                });
+                onchanged.emit(());
+            })
+        };
+        match chat_attachment::upload(&file, onprogress).await {
+            Ok(uploaded) => {
+                update_message(histories, room, client_id, |x| {
+                    if let Some(a) = x.attachments.get_mut(index) {
+                        a.url = uploaded.url;
+                        a.thumbnail_url = uploaded.thumbnail_url;
+                        a.progress = None;
+                    }
+                });
+            }
+            Err(err) => {
+                log::error!("upload {} error: {}", file.name(), err);
+                update_message(histories, room, client_id, |x| {
+                    if let Some(a) = x.attachments.get_mut(index) {
+                        a.progress = None;
+                    }
+                });
+                return false;
+            }
+        }
+    }
+    true
+}
+
+fn deliver(
+    histories: &Histories,
+    ws_writer: &WsWriter,
+    room: &str,
+    client_id: &str,
+    onchanged: Callback<()>,
+) {
+    let histories = histories.clone();
+    let ws_writer = ws_writer.clone();
+    let room = room.to_string();
+    let client_id = client_id.to_string();
+    spawn_local(async move {
+        if !upload_attachments(&histories, &room, &client_id, &onchanged).await {
+            set_status(&histories, &room, &client_id, DeliveryStatus::Failed);
+            onchanged.emit(());
+            return;
+        }
+        let message = update_message(&histories, &room, &client_id, |x| {
+            (x.content.clone(), x.attachments.clone())
+        });
+        if let Some((content, attachments)) = message {
+            transmit(
+                &histories,
+                &ws_writer,
+                &room,
+                &client_id,
+                &content,
+                &attachments,
+                onchanged,
+            );
+        }
+    });
+}
+
+pub fn send(
+    histories: &Histories,
+    ws_writer: &WsWriter,
+    room: &str,
+    from_name: &str,
+    content: &str,
+    attachments: Vec<Attachment>,
+    onchanged: Callback<()>,
+) {
+    let client_id = Uuid::new_v4().to_string();
+    histories
+        .borrow_mut()
+        .entry(room.to_string())
+        .or_default()
+        .push_live(MessageContent {
+            id: 0,
+            room: room.to_string(),
+            from_id: "".to_string(),
+            from_name: from_name.to_string(),
+            content: content.to_string(),
+            time: chrono::Local::now().to_rfc3339(),
+            is_own: Some(()),
+            client_id: Some(client_id.clone()),
+            status: Some(DeliveryStatus::Pending),
+            attachments,
+            edited_at: None,
+            deleted: false,
+        });
+    onchanged.emit(());
+    deliver(histories, ws_writer, room, &client_id, onchanged);
+}
+
+pub fn retry(
+    histories: &Histories,
+    ws_writer: &WsWriter,
+    room: &str,
+    client_id: &str,
+    onchanged: Callback<()>,
+) {
+    let failed = update_message(histories, room, client_id, |x| {
+        x.status == Some(DeliveryStatus::Failed)
+    })
+    .unwrap_or_default();
+    if failed {
+        set_status(histories, room, client_id, DeliveryStatus::Pending);
+        onchanged.emit(());
+        deliver(histories, ws_writer, room, client_id, onchanged);
+    }
+}
+
+// the server stored the message, the local echo takes over the server id and time
+pub fn ack(histories: &Histories, ack: Ack) {
+    {
+        let mut borrow = histories.borrow_mut();
+        let message = borrow.get_mut(&ack.room).and_then(|x| {
+            x.messages
+                .iter_mut()
+                .find(|x| x.client_id.as_deref() == Some(ack.client_id.as_str()))
+        });
+        if let Some(message) = message {
+            message.id = ack.id;
+            message.time = ack.time;
+            if message.status != Some(DeliveryStatus::Delivered) {
+                message.status = Some(DeliveryStatus::Sent);
+            }
+            message
+                .attachments
+                .iter_mut()
+                .for_each(Attachment::release_preview);
+        }
+    }
+    chat_history::persist(histories, &ack.room);
+}
+
+pub fn delivered(histories: &Histories, delivered: Delivered) {
+    {
+        let mut borrow = histories.borrow_mut();
+        let message = borrow
+            .get_mut(&delivered.room)
+            .and_then(|x| x.messages.iter_mut().find(|x| x.id == delivered.id));
+        if let Some(message) = message {
+            message.status = Some(DeliveryStatus::Delivered);
+        }
+    }
+    chat_history::persist(histories, &delivered.room);
+}
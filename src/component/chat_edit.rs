@@ -0,0 +1,85 @@
+use super::chat_delivery::{self, WsWriter};
+use super::chat_history::{self, Histories};
+use crate::util::common::{self, BasicResult};
+use serde::{Deserialize, Serialize};
+
+pub const EDIT_PRE: &str = "edit:";
+pub const DELETE_PRE: &str = "delete:";
+pub const EDITED_PRE: &str = "edited:";
+pub const DELETED_PRE: &str = "deleted:";
+
+const ADMIN_TYPE: &str = "admin";
+
+#[derive(Serialize)]
+struct OutgoingEdit<'a> {
+    room: &'a str,
+    id: u128,
+    content: &'a str,
+}
+
+#[derive(Serialize)]
+struct OutgoingDelete<'a> {
+    room: &'a str,
+    id: u128,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct Edited {
+    pub room: String,
+    pub id: u128,
+    pub content: String,
+    pub edited_at: String,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct Deleted {
+    pub room: String,
+    pub id: u128,
+}
+
+// admins may delete any message, the server checks it again
+pub fn can_moderate() -> bool {
+    common::get_current_user()
+        .map(|x| x.r#type == ADMIN_TYPE)
+        .unwrap_or_default()
+}
+
+// the change is applied once the server broadcasts it back
+pub fn edit(ws_writer: &WsWriter, room: &str, id: u128, content: &str) -> BasicResult<()> {
+    let frame = serde_json::to_string(&OutgoingEdit { room, id, content })?;
+    chat_delivery::send_frame(ws_writer, format!("{EDIT_PRE}{frame}"))
+}
+
+pub fn delete(ws_writer: &WsWriter, room: &str, id: u128) -> BasicResult<()> {
+    let frame = serde_json::to_string(&OutgoingDelete { room, id })?;
+    chat_delivery::send_frame(ws_writer, format!("{DELETE_PRE}{frame}"))
+}
+
+pub fn edited(histories: &Histories, edited: Edited) {
+    {
+        let mut borrow = histories.borrow_mut();
+        let message = borrow
+            .get_mut(&edited.room)
+            .and_then(|x| x.messages.iter_mut().find(|x| x.id == edited.id));
+        if let Some(message) = message {
+            message.content = edited.content;
+            message.edited_at = Some(edited.edited_at);
+        }
+    }
+    chat_history::persist(histories, &edited.room);
+}
+
+pub fn deleted(histories: &Histories, deleted: Deleted) {
+    {
+        let mut borrow = histories.borrow_mut();
+        let message = borrow
+            .get_mut(&deleted.room)
+            .and_then(|x| x.messages.iter_mut().find(|x| x.id == deleted.id));
+        if let Some(message) = message {
+            message.content.clear();
+            message.attachments.clear();
+            message.deleted = true;
+        }
+    }
+    chat_history::persist(histories, &deleted.room);
+}
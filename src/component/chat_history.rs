@@ -0,0 +1,243 @@
+use super::chat_attachment::Attachment;
+use super::message_list::{DeliveryStatus, MessageContent};
+use crate::util::common::BasicResult;
+use crate::util::idb;
+use crate::util::request::{self, Host};
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet, LinkedList};
+use std::rc::Rc;
+use wasm_bindgen_futures::spawn_local;
+use yew::Callback;
+
+pub const HISTORY_PAGE_SIZE: usize = 30;
+// latest messages of each room kept in indexed db
+const CACHE_SIZE: usize = 300;
+
+pub type Histories = Rc<RefCell<HashMap<String, RoomHistory>>>;
+
+pub struct RoomHistory {
+    pub messages: LinkedList<MessageContent>,
+    pub has_more: bool,
+    pub loading: bool,
+    pub loaded: bool,
+    pub unread: usize,
+    pub mentioned: bool,
+}
+
+impl Default for RoomHistory {
+    fn default() -> Self {
+        Self {
+            messages: Default::default(),
+            has_more: true,
+            loading: false,
+            loaded: false,
+            unread: 0,
+            mentioned: false,
+        }
+    }
+}
+
+impl RoomHistory {
+    // server ids are increasing, 0 means the message is not confirmed by server yet
+    pub fn oldest_id(&self) -> Option<u128> {
+        self.messages.iter().map(|x| x.id).filter(|&id| id != 0).min()
+    }
+
+    pub fn newest_id(&self) -> Option<u128> {
+        self.messages.iter().map(|x| x.id).filter(|&id| id != 0).max()
+    }
+
+    // returns false when the message was already fetched by a history page,
+    // or when it is the server copy of a local echo, which is replaced in place
+    pub fn push_live(&mut self, message: MessageContent) -> bool {
+        if let Some(client_id) = message.client_id.as_deref() {
+            let local = self
+                .messages
+                .iter_mut()
+                .find(|x| x.client_id.as_deref() == Some(client_id));
+            if let Some(local) = local {
+                local
+                    .attachments
+                    .iter_mut()
+                    .for_each(Attachment::release_preview);
+                let status = match local.status {
+                    Some(DeliveryStatus::Delivered) => DeliveryStatus::Delivered,
+                    _ => DeliveryStatus::Sent,
+                };
+                *local = MessageContent {
+                    is_own: Some(()),
+                    status: Some(status),
+                    ..message
+                };
+                return false;
+            }
+        }
+        if message.id != 0 && self.messages.iter().any(|x| x.id == message.id) {
+            return false;
+        }
+        self.messages.push_back(message);
+        true
+    }
+
+    pub fn merge_page(&mut self, page: Vec<MessageContent>) {
+        let ids = self.messages.iter().map(|x| x.id).collect::<HashSet<u128>>();
+        let mut page = page
+            .into_iter()
+            .filter(|x| x.id != 0 && !ids.contains(&x.id))
+            .collect::<Vec<MessageContent>>();
+        if page.is_empty() {
+            return;
+        }
+        // unconfirmed local messages stay at the tail
+        let (mut confirmed, pending): (Vec<MessageContent>, Vec<MessageContent>) =
+            std::mem::take(&mut self.messages)
+                .into_iter()
+                .partition(|x| x.id != 0);
+        confirmed.append(&mut page);
+        confirmed.sort_by_key(|x| x.id);
+        confirmed.dedup_by_key(|x| x.id);
+        self.messages = confirmed.into_iter().chain(pending).collect();
+    }
+
+    // the latest page does not reach the cached messages, drop them to avoid a gap
+    fn merge_latest(&mut self, page: Vec<MessageContent>) {
+        let page_oldest = page.iter().map(|x| x.id).min();
+        if let (Some(page_oldest), Some(newest)) = (page_oldest, self.newest_id()) {
+            if page.len() >= HISTORY_PAGE_SIZE && page_oldest > newest {
+                self.messages = std::mem::take(&mut self.messages)
+                    .into_iter()
+                    .filter(|x| x.id == 0)
+                    .collect();
+            }
+        }
+        self.merge_page(page);
+    }
+
+    fn cache_value(&self) -> Vec<MessageContent> {
+        let confirmed = self
+            .messages
+            .iter()
+            .filter(|x| x.id != 0)
+            .cloned()
+            .collect::<Vec<MessageContent>>();
+        let skip = confirmed.len().saturating_sub(CACHE_SIZE);
+        confirmed.into_iter().skip(skip).collect()
+    }
+}
+
+async fn fetch_page(room: &str, before: Option<u128>) -> BasicResult<Vec<MessageContent>> {
+    let mut params = vec![
+        ("room", room.to_string()),
+        ("size", HISTORY_PAGE_SIZE.to_string()),
+    ];
+    if let Some(before) = before {
+        params.push(("before", before.to_string()));
+    }
+    let res = request::get::<Vec<MessageContent>, Vec<(&str, String)>, _>(
+        Host::ApiBase,
+        "/chat/history",
+        Some(params),
+    )
+    .await?;
+    Ok(res.data.unwrap_or_default())
+}
+
+pub async fn load_cache(room: &str) -> BasicResult<Vec<MessageContent>> {
+    match idb::get(idb::CHAT_HISTORY_STORE, room).await? {
+        Some(v) => Ok(serde_json::from_str(&v)?),
+        None => Ok(vec![]),
+    }
+}
+
+pub fn persist(histories: &Histories, room: &str) {
+    let value = match histories.borrow().get(room) {
+        Some(history) => history.cache_value(),
+        None => return,
+    };
+    let room = room.to_string();
+    spawn_local(async move {
+        let res = match serde_json::to_string(&value) {
+            Ok(v) => idb::put(idb::CHAT_HISTORY_STORE, &room, &v).await,
+            Err(err) => Err(err.into()),
+        };
+        if let Err(err) = res {
+            log::warn!("save chat history of {} error: {}", room, err);
+        }
+    });
+}
+
+// first open of a room: cached messages are shown at once, then the latest page is fetched
+pub fn load_room(histories: &Histories, room: &str, onloaded: Callback<()>) {
+    {
+        let mut borrow = histories.borrow_mut();
+        let history = borrow.entry(room.to_string()).or_default();
+        if history.loaded || history.loading {
+            return;
+        }
+        history.loading = true;
+    }
+    let histories = histories.clone();
+    let room = room.to_string();
+    spawn_local(async move {
+        match load_cache(&room).await {
+            Ok(cached) => {
+                histories
+                    .borrow_mut()
+                    .entry(room.clone())
+                    .or_default()
+                    .merge_page(cached);
+                onloaded.emit(());
+            }
+            Err(err) => log::warn!("load chat history cache of {} error: {}", room, err),
+        }
+
+        let res = fetch_page(&room, None).await;
+        {
+            let mut borrow = histories.borrow_mut();
+            let history = borrow.entry(room.clone()).or_default();
+            history.loading = false;
+            match res {
+                Ok(page) => {
+                    history.loaded = true;
+                    history.has_more = page.len() >= HISTORY_PAGE_SIZE;
+                    history.merge_latest(page);
+                }
+                Err(err) => log::error!("fetch chat history of {} error: {}", room, err),
+            }
+        }
+        persist(&histories, &room);
+        onloaded.emit(());
+    });
+}
+
+// fetch the page before the oldest loaded message
+pub fn load_more(histories: &Histories, room: &str, onloaded: Callback<()>) {
+    let before = {
+        let mut borrow = histories.borrow_mut();
+        let history = borrow.entry(room.to_string()).or_default();
+        if !history.loaded || !history.has_more || history.loading {
+            return;
+        }
+        history.loading = true;
+        history.oldest_id()
+    };
+    let histories = histories.clone();
+    let room = room.to_string();
+    spawn_local(async move {
+        let res = fetch_page(&room, before).await;
+        {
+            let mut borrow = histories.borrow_mut();
+            let history = borrow.entry(room.clone()).or_default();
+            history.loading = false;
+            match res {
+                Ok(page) => {
+                    history.has_more = page.len() >= HISTORY_PAGE_SIZE;
+                    history.merge_page(page);
+                }
+                Err(err) => log::error!("fetch chat history of {} error: {}", room, err),
+            }
+        }
+        persist(&histories, &room);
+        onloaded.emit(());
+    });
+}
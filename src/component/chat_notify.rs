@@ -0,0 +1,101 @@
+use super::chat_history::Histories;
+use crate::util::{common, markdown};
+use std::collections::HashSet;
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::JsCast;
+use web_sys::{Notification, NotificationOptions, NotificationPermission};
+use yew::UseStateHandle;
+
+const MUTED_ROOMS_KEY: &str = "muted_rooms";
+
+#[derive(Clone, PartialEq, Default, Debug)]
+pub struct ChatUnread {
+    pub total: usize,
+    pub mentioned: bool,
+}
+
+// provided by Layout, so that the header can show what MessageList counts
+pub type ChatUnreadHandle = UseStateHandle<ChatUnread>;
+
+fn muted_rooms() -> HashSet<String> {
+    common::get_local_storage(MUTED_ROOMS_KEY)
+        .and_then(|x| serde_json::from_str(&x).ok())
+        .unwrap_or_default()
+}
+
+pub fn is_muted(room: &str) -> bool {
+    muted_rooms().contains(room)
+}
+
+pub fn set_muted(room: &str, muted: bool) {
+    let mut rooms = muted_rooms();
+    if muted {
+        rooms.insert(room.to_string());
+    } else {
+        rooms.remove(room);
+    }
+    common::set_local_storage(MUTED_ROOMS_KEY, &serde_json::to_string(&rooms).unwrap());
+}
+
+pub fn is_mentioned(text: &str, name: &str) -> bool {
+    !markdown::find_mentions(text, name).is_empty()
+}
+
+pub fn is_hidden() -> bool {
+    web_sys::window()
+        .and_then(|x| x.document())
+        .map(|x| x.hidden())
+        .unwrap_or_default()
+}
+
+// has to be called from a user gesture, e.g. opening the dialog
+pub fn request_permission() {
+    if Notification::permission() == NotificationPermission::Default {
+        if let Err(err) = Notification::request_permission() {
+            log::warn!("request notification permission error: {:?}", err);
+        }
+    }
+}
+
+pub fn notify(room: &str, from: &str, content: &str) {
+    if Notification::permission() != NotificationPermission::Granted {
+        return;
+    }
+    let options = NotificationOptions::new();
+    options.set_body(&format!("{from}: {content}"));
+    options.set_tag(room);
+    match Notification::new_with_options(room, &options) {
+        Ok(notification) => {
+            // freed by the click, a notification is clicked at most once
+            let onclick = Closure::once_into_js(move || {
+                if let Some(window) = web_sys::window() {
+                    window.focus().unwrap_or_default();
+                }
+            });
+            notification.set_onclick(Some(onclick.unchecked_ref()));
+        }
+        Err(err) => log::warn!("show notification error: {:?}", err),
+    }
+}
+
+pub fn mark_read(histories: &Histories, room: &str) {
+    if let Some(history) = histories.borrow_mut().get_mut(room) {
+        history.unread = 0;
+        history.mentioned = false;
+    }
+}
+
+pub fn publish(unread: &Option<ChatUnreadHandle>, histories: &Histories) {
+    if let Some(unread) = unread {
+        let mut value = ChatUnread::default();
+        for (room, history) in histories.borrow().iter() {
+            if history.mentioned || (history.unread > 0 && !is_muted(room)) {
+                value.total += history.unread;
+                value.mentioned |= history.mentioned;
+            }
+        }
+        if **unread != value {
+            unread.set(value);
+        }
+    }
+}
@@ -0,0 +1,203 @@
+use super::chat_delivery::{self, WsWriter};
+use super::chat_notify;
+use crate::util::i18n;
+use gloo::events::EventListener;
+use gloo::timers::callback::{Interval, Timeout};
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+use yew::Callback;
+
+pub const PRESENCE_PRE: &str = "presence:";
+pub const TYPING_PRE: &str = "typing:";
+
+// at most one typing frame is sent in this interval
+const TYPING_THROTTLE_MILLIS: i64 = 3000;
+// a typing indicator disappears when no new frame arrives in this interval
+const TYPING_EXPIRE_MILLIS: i64 = 5000;
+const IDLE_MILLIS: i64 = 5 * 60 * 1000;
+const IDLE_CHECK_MILLIS: u32 = 30 * 1000;
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Presence {
+    // sessions listed in a room are connected
+    #[default]
+    Online,
+    Away,
+    Offline,
+}
+
+impl Presence {
+    pub fn icon_class(&self) -> &'static str {
+        match self {
+            Presence::Online => "fa-solid fa-circle presence-online",
+            Presence::Away => "fa-solid fa-circle presence-away",
+            Presence::Offline => "fa-regular fa-circle presence-offline",
+        }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+pub struct PresenceChange {
+    pub session_id: String,
+    pub status: Presence,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct Typing {
+    pub session_id: String,
+    pub name: String,
+    pub room: String,
+}
+
+#[derive(Serialize)]
+struct OutgoingTyping<'a> {
+    room: &'a str,
+}
+
+#[derive(Serialize)]
+struct OutgoingPresence {
+    status: Presence,
+}
+
+pub type Presences = Rc<RefCell<HashMap<String, Presence>>>;
+
+#[derive(Default)]
+pub struct TypingState {
+    // room -> session id -> (name, expires at)
+    rooms: HashMap<String, HashMap<String, (String, i64)>>,
+    last_sent: HashMap<String, i64>,
+}
+
+pub type Typings = Rc<RefCell<TypingState>>;
+
+fn now() -> i64 {
+    chrono::Utc::now().timestamp_millis()
+}
+
+pub fn on_presence(presences: &Presences, change: PresenceChange) {
+    presences
+        .borrow_mut()
+        .insert(change.session_id, change.status);
+}
+
+pub fn on_typing(typings: &Typings, typing: Typing, onchanged: Callback<()>) {
+    let room = typing.room.clone();
+    typings
+        .borrow_mut()
+        .rooms
+        .entry(typing.room)
+        .or_default()
+        .insert(
+            typing.session_id,
+            (typing.name, now() + TYPING_EXPIRE_MILLIS),
+        );
+    onchanged.emit(());
+
+    let typings = typings.clone();
+    Timeout::new(TYPING_EXPIRE_MILLIS as u32, move || {
+        let now = now();
+        if let Some(sessions) = typings.borrow_mut().rooms.get_mut(&room) {
+            sessions.retain(|_, (_, expires_at)| *expires_at > now);
+        }
+        onchanged.emit(());
+    })
+    .forget();
+}
+
+// the message itself ends the typing indicator of its sender
+pub fn clear_typing(typings: &Typings, room: &str, session_id: &str) {
+    if let Some(sessions) = typings.borrow_mut().rooms.get_mut(room) {
+        sessions.remove(session_id);
+    }
+}
+
+pub fn typing_text(typings: &Typings, room: &str) -> Option<String> {
+    let borrow = typings.borrow();
+    let mut names = borrow
+        .rooms
+        .get(room)?
+        .values()
+        .map(|(name, _)| name.clone())
+        .collect::<Vec<String>>();
+    names.sort();
+    match names.len() {
+        0 => None,
+        1 => Some(i18n::t_with("chat.typing.one", &[("name", &names[0])])),
+        2 => Some(i18n::t_with(
+            "chat.typing.two",
+            &[("first", &names[0]), ("second", &names[1])],
+        )),
+        _ => Some(i18n::t("chat.typing.many")),
+    }
+}
+
+pub fn send_typing(typings: &Typings, ws_writer: &WsWriter, room: &str) {
+    let now = now();
+    {
+        let mut borrow = typings.borrow_mut();
+        let last_sent = borrow.last_sent.entry(room.to_string()).or_default();
+        if now - *last_sent < TYPING_THROTTLE_MILLIS {
+            return;
+        }
+        *last_sent = now;
+    }
+    let frame = serde_json::to_string(&OutgoingTyping { room }).unwrap();
+    chat_delivery::send_frame(ws_writer, format!("{TYPING_PRE}{frame}"))
+        .unwrap_or_else(|e| log::warn!("send typing error: {}", e));
+}
+
+// reports away after a period without input or while the tab is hidden
+pub struct IdleWatcher {
+    _listeners: Vec<EventListener>,
+    _interval: Interval,
+}
+
+impl IdleWatcher {
+    pub fn new(ws_writer: &WsWriter) -> Self {
+        let last_active = Rc::new(RefCell::new(now()));
+        let status = Rc::new(RefCell::new(Presence::Online));
+
+        let check = {
+            let ws_writer = ws_writer.clone();
+            let last_active = last_active.clone();
+            let status = status.clone();
+            Rc::new(move || {
+                let current =
+                    if chat_notify::is_hidden() || now() - *last_active.borrow() > IDLE_MILLIS {
+                        Presence::Away
+                    } else {
+                        Presence::Online
+                    };
+                if *status.borrow() != current {
+                    *status.borrow_mut() = current;
+                    let frame =
+                        serde_json::to_string(&OutgoingPresence { status: current }).unwrap();
+                    chat_delivery::send_frame(&ws_writer, format!("{PRESENCE_PRE}{frame}"))
+                        .unwrap_or_else(|e| log::warn!("send presence error: {}", e));
+                }
+            })
+        };
+
+        let document = web_sys::window().unwrap().document().unwrap();
+        let listeners = ["mousemove", "keydown", "visibilitychange"]
+            .iter()
+            .map(|event| {
+                let last_active = last_active.clone();
+                let check = check.clone();
+                EventListener::new(&document, *event, move |_| {
+                    *last_active.borrow_mut() = now();
+                    check();
+                })
+            })
+            .collect();
+        let interval = Interval::new(IDLE_CHECK_MILLIS, move || check());
+
+        Self {
+            _listeners: listeners,
+            _interval: interval,
+        }
+    }
+}
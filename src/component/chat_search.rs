@@ -0,0 +1,81 @@
+use super::chat_history::{self, Histories};
+use super::message_list::MessageContent;
+use crate::util::common::BasicResult;
+use crate::util::request::{self, Host};
+use std::collections::HashMap;
+
+const SEARCH_SIZE: usize = 50;
+
+fn matches(message: &MessageContent, keyword: &str) -> bool {
+    !message.deleted && message.content.to_lowercase().contains(keyword)
+}
+
+async fn search_server(keyword: &str, room: Option<&str>) -> BasicResult<Vec<MessageContent>> {
+    let mut params = vec![
+        ("keyword", keyword.to_string()),
+        ("size", SEARCH_SIZE.to_string()),
+    ];
+    if let Some(room) = room {
+        params.push(("room", room.to_string()));
+    }
+    let res = request::get::<Vec<MessageContent>, Vec<(&str, String)>, _>(
+        Host::ApiBase,
+        "/chat/search",
+        Some(params),
+    )
+    .await?;
+    Ok(res.data.unwrap_or_default())
+}
+
+// searches loaded messages and the indexed db cache of the given rooms
+async fn search_local(
+    histories: &Histories,
+    keyword: &str,
+    rooms: Vec<String>,
+) -> Vec<MessageContent> {
+    let keyword = keyword.to_lowercase();
+    let mut found: HashMap<(String, u128), MessageContent> = HashMap::new();
+    for room in rooms {
+        let loaded = histories
+            .borrow()
+            .get(&room)
+            .map(|x| x.messages.iter().cloned().collect::<Vec<MessageContent>>())
+            .unwrap_or_default();
+        let cached = chat_history::load_cache(&room).await.unwrap_or_else(|err| {
+            log::warn!("load chat history cache of {} error: {}", room, err);
+            vec![]
+        });
+        for message in loaded.into_iter().chain(cached) {
+            if message.id != 0 && matches(&message, &keyword) {
+                found.insert((message.room.clone(), message.id), message);
+            }
+        }
+    }
+    let mut res = found.into_values().collect::<Vec<MessageContent>>();
+    res.sort_by(|a, b| b.id.cmp(&a.id));
+    res.truncate(SEARCH_SIZE);
+    res
+}
+
+// returns the matched messages, newest first, and whether they come from the local cache
+pub async fn search(
+    histories: &Histories,
+    keyword: &str,
+    room: Option<&str>,
+    rooms: Vec<String>,
+) -> (Vec<MessageContent>, bool) {
+    match search_server(keyword, room).await {
+        Ok(res) => (res, false),
+        Err(err) => {
+            log::warn!(
+                "search chat messages error: {}, fall back to local cache",
+                err
+            );
+            let rooms = match room {
+                Some(room) => vec![room.to_string()],
+                None => rooms,
+            };
+            (search_local(histories, keyword, rooms).await, true)
+        }
+    }
+}
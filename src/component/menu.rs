@@ -20,10 +20,18 @@ pub struct MenuLabel {
     pub nodes: Vec<MenuNode>,
 }
 
-#[derive(Clone, PartialEq, Serialize, Deserialize, Debug)]
+#[derive(Clone, PartialEq, Serialize, Deserialize, Debug, Default)]
 pub struct MenuNode {
     pub name: String,
     pub children: Vec<MenuNode>,
+    // e.g. unread count of a chat room
+    #[serde(default)]
+    pub badge: Option<String>,
+    #[serde(default)]
+    pub highlight: bool,
+    // icon class shown before the name
+    #[serde(default)]
+    pub icon: Option<String>,
 }
 
 impl MenuNode {
@@ -43,7 +51,27 @@ impl MenuNode {
 
         html! {
             <li>
-                <a href={String::from("javascript:void(0)")} class={class} onclick = {onclick}>{&self.name}</a>
+                <a href={String::from("javascript:void(0)")} class={class} onclick = {onclick}>
+                    {
+                        if let Some(icon) = &self.icon {
+                            html!{
+                                <span class="icon is-small menu-icon"><i class={icon.clone()}></i></span>
+                            }
+                        } else {
+                            html!{}
+                        }
+                    }
+                    {&self.name}
+                    {
+                        if let Some(badge) = &self.badge {
+                            html!{
+                                <span class={classes!("tag", "is-rounded", "menu-badge", if self.highlight {"is-danger"} else {"is-info"})}>{badge}</span>
+                            }
+                        } else {
+                            html!{}
+                        }
+                    }
+                </a>
                 {
                     if self.children.is_empty() {
                         html!{}
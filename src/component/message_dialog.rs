@@ -1,14 +1,23 @@
+use crate::component::chat_attachment::{self, Attachment, MAX_ATTACHMENTS};
+use crate::component::chat_delivery::{self, WsWriter};
+use crate::component::chat_edit;
+use crate::component::chat_history::{self, Histories};
+use crate::component::chat_notify::{self, ChatUnreadHandle};
+use crate::component::chat_presence::{self, Presences, Typings};
+use crate::component::chat_search;
 use crate::component::menu::{Menu, MenuLabel, MenuNode};
 use crate::component::message_list::MessageContent;
-use futures::stream::SplitSink;
-use futures::SinkExt;
-use gloo_net::websocket::{futures::WebSocket, Message};
+use crate::component::message_timeline::MessageTimeline;
+use crate::confirm_form::ConfirmForm;
+use crate::util::i18n::use_translation;
+use crate::util::time;
+use gloo::events::EventListener;
 use std::cell::RefCell;
 use std::collections::HashMap;
-use std::collections::LinkedList;
 use std::rc::Rc;
+use wasm_bindgen::JsCast;
 use wasm_bindgen_futures::spawn_local;
-use web_sys::HtmlInputElement;
+use web_sys::{ClipboardEvent, FileList, HtmlInputElement, HtmlTextAreaElement};
 use yew::prelude::*;
 use yew::Properties;
 
@@ -21,10 +30,16 @@ pub struct MessageDialogProps {
     pub session_id: String,
 
     #[prop_or_default]
-    pub messages: Rc<RefCell<HashMap<String, LinkedList<MessageContent>>>>,
+    pub messages: Histories,
 
     #[prop_or_default]
-    pub ws_writer: Rc<RefCell<Option<SplitSink<WebSocket, Message>>>>,
+    pub presences: Presences,
+
+    #[prop_or_default]
+    pub typings: Typings,
+
+    #[prop_or_default]
+    pub ws_writer: WsWriter,
 
     #[prop_or_default]
     pub current_room: Rc<RefCell<Option<String>>>,
@@ -38,11 +53,23 @@ impl PartialEq for MessageDialogProps {
     }
 }
 
+#[derive(Default)]
+struct SearchState {
+    all_rooms: bool,
+    searching: bool,
+    // None until the first search is done
+    results: Option<Vec<MessageContent>>,
+    from_cache: bool,
+}
+
 #[function_component(MessageDialog)]
 pub fn message_dialog(props: &MessageDialogProps) -> Html {
     let force_update = use_force_update();
+    let unread = use_context::<ChatUnreadHandle>();
+    let i18n = use_translation();
+    let dialog_title = i18n.t("chat.dialog");
     let borrow = props.current_room.borrow();
-    let title = borrow.as_deref().unwrap_or("Dialog");
+    let title = borrow.as_deref().unwrap_or(&dialog_title);
     let closedialog = {
         let onclose = props.onclose.clone();
         Callback::from(move |_| {
@@ -54,9 +81,20 @@ pub fn message_dialog(props: &MessageDialogProps) -> Html {
         .rooms
         .borrow()
         .iter()
-        .map(|(room, _)| MenuNode {
-            name: room.to_string(),
-            children: vec![],
+        .map(|(room, _)| {
+            let (count, mentioned) = props
+                .messages
+                .borrow()
+                .get(room)
+                .map(|x| (x.unread, x.mentioned))
+                .unwrap_or_default();
+            MenuNode {
+                name: room.to_string(),
+                children: vec![],
+                badge: (count > 0).then(|| count.to_string()),
+                highlight: mentioned,
+                ..Default::default()
+            }
         })
         .collect::<Vec<MenuNode>>();
 
@@ -74,50 +112,130 @@ pub fn message_dialog(props: &MessageDialogProps) -> Html {
         })
     };
 
-    let ref1 = use_node_ref();
     let ref2 = use_node_ref();
+    let file_ref = use_node_ref();
+    // files picked, dropped or pasted, sent together with the next message
+    let staged: Rc<RefCell<Vec<Attachment>>> = use_mut_ref(Vec::new);
+    let attach_error: Rc<RefCell<Option<String>>> = use_mut_ref(|| None);
+
+    let add_files = {
+        let staged = staged.clone();
+        let attach_error = attach_error.clone();
+        let force_update = force_update.clone();
+        Callback::from(move |files: FileList| {
+            let mut error = None;
+            for i in 0..files.length() {
+                let file = match files.get(i) {
+                    Some(file) => file,
+                    None => continue,
+                };
+                if staged.borrow().len() >= MAX_ATTACHMENTS {
+                    error = Some(format!("at most {} files in one message", MAX_ATTACHMENTS));
+                    break;
+                }
+                match chat_attachment::validate(&file) {
+                    Ok(_) => staged.borrow_mut().push(Attachment::from_file(file)),
+                    Err(err) => error = Some(err.to_string()),
+                }
+            }
+            *attach_error.borrow_mut() = error;
+            force_update.force_update();
+        })
+    };
+
+    let drag_over = Callback::from(|e: DragEvent| e.prevent_default());
+
+    let drop_files = {
+        let add_files = add_files.clone();
+        Callback::from(move |e: DragEvent| {
+            e.prevent_default();
+            if let Some(files) = e.data_transfer().and_then(|x| x.files()) {
+                add_files.emit(files);
+            }
+        })
+    };
+
+    let paste_files = {
+        let add_files = add_files.clone();
+        Callback::from(move |e: Event| {
+            let files = e
+                .dyn_ref::<ClipboardEvent>()
+                .and_then(|x| x.clipboard_data())
+                .and_then(|x| x.files())
+                .filter(|x| x.length() > 0);
+            // plain text is pasted as usual
+            if let Some(files) = files {
+                e.prevent_default();
+                add_files.emit(files);
+            }
+        })
+    };
+
+    let pick_files = {
+        let file_ref = file_ref.clone();
+        Callback::from(move |_| {
+            if let Some(input) = file_ref.cast::<HtmlInputElement>() {
+                input.click();
+            }
+        })
+    };
+
+    let choose_files = {
+        let add_files = add_files.clone();
+        Callback::from(move |e: Event| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            if let Some(files) = input.files() {
+                add_files.emit(files);
+            }
+            input.set_value("");
+        })
+    };
+
+    // the message being edited takes the place of a new message in the textarea
+    let editing: Rc<RefCell<Option<MessageContent>>> = use_mut_ref(|| None);
+    let deleting: Rc<RefCell<Option<MessageContent>>> = use_mut_ref(|| None);
 
     let key_send = {
         let force_update = force_update.clone();
         let ref2 = ref2.clone();
+        let staged = staged.clone();
+        let editing = editing.clone();
         let messages = props.messages.clone();
         let current_room = props.current_room.clone();
         let ws_writer = props.ws_writer.clone();
         let session_id = props.session_id.clone();
         Callback::from(move |e: KeyboardEvent| {
-            if e.key_code() == 13 {
+            if e.key_code() == 27 && editing.borrow().is_some() {
+                *editing.borrow_mut() = None;
+                ref2.cast::<HtmlTextAreaElement>().unwrap().set_value("");
+                force_update.force_update();
+            } else if e.key_code() == 13 {
                 let message_input = &ref2;
-                let input = message_input.cast::<HtmlInputElement>().unwrap();
+                let input = message_input.cast::<HtmlTextAreaElement>().unwrap();
                 let content = input.value();
                 if !e.meta_key() {
                     // dot not with ALT
                     e.prevent_default();
-                    if !content.trim_matches('\n').trim().is_empty() {
+                    let content = content.trim_matches('\n').trim();
+                    if let Some(message) = editing.borrow_mut().take() {
+                        if !content.is_empty() && content != message.content {
+                            chat_edit::edit(&ws_writer, &message.room, message.id, content)
+                                .unwrap_or_else(|e| log::error!("edit message error: {}", e));
+                        }
+                        force_update.force_update();
+                    } else if !content.is_empty() || !staged.borrow().is_empty() {
                         if let Some(room) = current_room.borrow().as_deref() {
-                            let room = room.to_string();
-                            let session_id = session_id.clone();
-                            let messages = messages.clone();
                             let force_update = force_update.clone();
-                            let ws_writer = ws_writer.clone();
-                            spawn_local(async move {
-                                if let Some(ss) = &mut *ws_writer.borrow_mut() {
-                                    ss.send(Message::Text(content.clone())).await.unwrap();
-                                    messages
-                                        .borrow_mut()
-                                        .entry(room.clone())
-                                        .or_insert(Default::default())
-                                        .push_back(MessageContent {
-                                            id: 0,
-                                            room: room.to_string(),
-                                            from_id: "".to_string(),
-                                            from_name: session_id,
-                                            content: content.clone(),
-                                            time: "".to_string(), //chrono::Utc::now().to_default(),
-                                            is_own: Some(()),
-                                        });
-                                    force_update.force_update()
-                                }
-                            });
+                            let attachments = std::mem::take(&mut *staged.borrow_mut());
+                            chat_delivery::send(
+                                &messages,
+                                &ws_writer,
+                                room,
+                                &session_id,
+                                content,
+                                attachments,
+                                Callback::from(move |_| force_update.force_update()),
+                            );
                         }
                     }
                     input.set_value("");
@@ -129,14 +247,32 @@ pub fn message_dialog(props: &MessageDialogProps) -> Html {
         })
     };
 
+    let input_typing = {
+        let typings = props.typings.clone();
+        let current_room = props.current_room.clone();
+        let ws_writer = props.ws_writer.clone();
+        Callback::from(move |_: InputEvent| {
+            if let Some(room) = current_room.borrow().as_deref() {
+                chat_presence::send_typing(&typings, &ws_writer, room);
+            }
+        })
+    };
+
     let mut session_nodes = vec![];
 
     if let Some(room) = props.current_room.borrow().as_deref() {
         if let Some(sessions) = props.rooms.borrow().get(room) {
-            for (_, name) in sessions.iter() {
+            for (sid, name) in sessions.iter() {
+                let presence = props
+                    .presences
+                    .borrow()
+                    .get(sid)
+                    .copied()
+                    .unwrap_or_default();
                 session_nodes.push(MenuNode {
                     name: name.to_string(),
-                    children: vec![],
+                    icon: Some(presence.icon_class().to_string()),
+                    ..Default::default()
                 });
             }
         }
@@ -149,62 +285,402 @@ pub fn message_dialog(props: &MessageDialogProps) -> Html {
 
     {
         let messages = props.messages.clone();
-        let message_input = ref1.clone();
+        let force_update = force_update.clone();
+        let unread = unread.clone();
+        let room = props.current_room.borrow().clone();
+        use_effect_with(room, move |room| {
+            if let Some(room) = room {
+                chat_notify::mark_read(&messages, room);
+                chat_notify::publish(&unread, &messages);
+                chat_history::load_room(
+                    &messages,
+                    room,
+                    Callback::from(move |_| force_update.force_update()),
+                );
+            }
+        });
+    }
+
+    let load_more = {
+        let messages = props.messages.clone();
         let current_room = props.current_room.clone();
-        use_effect(move || {
-            message_input
-                .cast::<HtmlInputElement>()
-                .and_then::<(), _>(|input| {
-                    let msg = match current_room.borrow().as_deref() {
-                        Some(room) => messages
-                            .borrow()
-                            .get(room)
-                            .unwrap_or(&Default::default())
-                            .iter()
-                            .map(|x| format!("{}: {}\n\n", x.from_name, x.content))
-                            .collect::<String>(),
-                        None => "".to_string(),
-                    };
-                    input.set_value(&msg);
-                    input.set_scroll_top(input.scroll_height());
-                    None
-                });
+        let force_update = force_update.clone();
+        Callback::from(move |_| {
+            if let Some(room) = current_room.borrow().as_deref() {
+                let force_update = force_update.clone();
+                chat_history::load_more(
+                    &messages,
+                    room,
+                    Callback::from(move |_| force_update.force_update()),
+                );
+            }
+        })
+    };
+
+    let retry = {
+        let messages = props.messages.clone();
+        let current_room = props.current_room.clone();
+        let ws_writer = props.ws_writer.clone();
+        let force_update = force_update.clone();
+        Callback::from(move |client_id: String| {
+            if let Some(room) = current_room.borrow().as_deref() {
+                let force_update = force_update.clone();
+                chat_delivery::retry(
+                    &messages,
+                    &ws_writer,
+                    room,
+                    &client_id,
+                    Callback::from(move |_| force_update.force_update()),
+                );
+            }
+        })
+    };
+
+    // messages that arrived while the tab was hidden are read once it is shown again
+    {
+        let messages = props.messages.clone();
+        let current_room = props.current_room.clone();
+        let unread = unread.clone();
+        let force_update = force_update.clone();
+        use_effect_with((), move |_| {
+            let document = web_sys::window().unwrap().document().unwrap();
+            let listener = EventListener::new(&document, "visibilitychange", move |_| {
+                if chat_notify::is_hidden() {
+                    return;
+                }
+                if let Some(room) = current_room.borrow().as_deref() {
+                    chat_notify::mark_read(&messages, room);
+                    chat_notify::publish(&unread, &messages);
+                    force_update.force_update();
+                }
+            });
+            move || drop(listener)
         });
     }
+
+    let edit = {
+        let editing = editing.clone();
+        let ref2 = ref2.clone();
+        let force_update = force_update.clone();
+        Callback::from(move |message: MessageContent| {
+            if let Some(input) = ref2.cast::<HtmlTextAreaElement>() {
+                input.set_value(&message.content);
+                input.focus().unwrap_or_default();
+            }
+            *editing.borrow_mut() = Some(message);
+            force_update.force_update();
+        })
+    };
+
+    let delete = {
+        let deleting = deleting.clone();
+        let force_update = force_update.clone();
+        Callback::from(move |message: MessageContent| {
+            *deleting.borrow_mut() = Some(message);
+            force_update.force_update();
+        })
+    };
+
+    let confirm_delete = {
+        let deleting = deleting.clone();
+        let ws_writer = props.ws_writer.clone();
+        let force_update = force_update.clone();
+        Callback::from(move |_| {
+            if let Some(message) = deleting.borrow_mut().take() {
+                chat_edit::delete(&ws_writer, &message.room, message.id)
+                    .unwrap_or_else(|e| log::error!("delete message error: {}", e));
+            }
+            force_update.force_update();
+        })
+    };
+
+    let cancel_delete = {
+        let deleting = deleting.clone();
+        let force_update = force_update.clone();
+        Callback::from(move |_| {
+            *deleting.borrow_mut() = None;
+            force_update.force_update();
+        })
+    };
+
+    let search_ref = use_node_ref();
+    let search: Rc<RefCell<SearchState>> = use_mut_ref(Default::default);
+
+    let key_search = {
+        let search = search.clone();
+        let search_ref = search_ref.clone();
+        let messages = props.messages.clone();
+        let rooms = props.rooms.clone();
+        let current_room = props.current_room.clone();
+        let force_update = force_update.clone();
+        Callback::from(move |e: KeyboardEvent| {
+            if e.key_code() != 13 {
+                return;
+            }
+            let keyword = search_ref
+                .cast::<HtmlInputElement>()
+                .map(|x| x.value().trim().to_string())
+                .unwrap_or_default();
+            if keyword.is_empty() || search.borrow().searching {
+                return;
+            }
+            let room = match search.borrow().all_rooms {
+                true => None,
+                false => current_room.borrow().clone(),
+            };
+            let rooms = rooms.borrow().keys().cloned().collect::<Vec<String>>();
+            search.borrow_mut().searching = true;
+            force_update.force_update();
+
+            let search = search.clone();
+            let messages = messages.clone();
+            let force_update = force_update.clone();
+            spawn_local(async move {
+                let (results, from_cache) =
+                    chat_search::search(&messages, &keyword, room.as_deref(), rooms).await;
+                let mut borrow = search.borrow_mut();
+                borrow.searching = false;
+                borrow.results = Some(results);
+                borrow.from_cache = from_cache;
+                drop(borrow);
+                force_update.force_update();
+            });
+        })
+    };
+
+    let toggle_all_rooms = {
+        let search = search.clone();
+        let force_update = force_update.clone();
+        Callback::from(move |_| {
+            let all_rooms = search.borrow().all_rooms;
+            search.borrow_mut().all_rooms = !all_rooms;
+            force_update.force_update();
+        })
+    };
+
+    let close_search = {
+        let search = search.clone();
+        let search_ref = search_ref.clone();
+        let force_update = force_update.clone();
+        Callback::from(move |_| {
+            search.borrow_mut().results = None;
+            if let Some(input) = search_ref.cast::<HtmlInputElement>() {
+                input.set_value("");
+            }
+            force_update.force_update();
+        })
+    };
+
+    let open_result = {
+        let search = search.clone();
+        let current_room = props.current_room.clone();
+        let force_update = force_update.clone();
+        Callback::from(move |room: String| {
+            search.borrow_mut().results = None;
+            *current_room.borrow_mut() = Some(room);
+            force_update.force_update();
+        })
+    };
+
+    let search_view = match &search.borrow().results {
+        Some(results) => {
+            let from_cache = search.borrow().from_cache;
+            html! {
+                <div class="search-results">
+                    <p class="search-summary">
+                        {i18n.t_plural("chat.results", results.len() as i64)}
+                        {
+                            if from_cache {
+                                html!{ <span class="tag is-warning is-light">{i18n.t("chat.from_cache")}</span> }
+                            } else {
+                                html!{}
+                            }
+                        }
+                        <button class="delete is-small" onclick={close_search}></button>
+                    </p>
+                    {
+                        results.iter().map(|x| {
+                            let onclick = {
+                                let open_result = open_result.clone();
+                                let room = x.room.clone();
+                                Callback::from(move |_| open_result.emit(room.clone()))
+                            };
+                            let parsed = time::parse(&x.time);
+                            let time = parsed.as_ref().map(time::format_short).unwrap_or_default();
+                            let utc = parsed.as_ref().map(time::format_utc).unwrap_or_default();
+                            html!{
+                                <a class="search-result" href={String::from("javascript:void(0)")} {onclick}>
+                                    <p class="timeline-sender">
+                                        <b>{&x.from_name}</b>
+                                        <span class="timeline-time" title={utc}>{format!("#{} {}", x.room, time)}</span>
+                                    </p>
+                                    <p>{&x.content}</p>
+                                </a>
+                            }
+                        }).collect::<Html>()
+                    }
+                </div>
+            }
+        }
+        None => html! {},
+    };
+    let (searching, all_rooms) = {
+        let borrow = search.borrow();
+        (borrow.searching, borrow.all_rooms)
+    };
+    let showing_results = search.borrow().results.is_some();
+
+    let muted = props
+        .current_room
+        .borrow()
+        .as_deref()
+        .map(chat_notify::is_muted)
+        .unwrap_or_default();
+
+    let toggle_mute = {
+        let current_room = props.current_room.clone();
+        let messages = props.messages.clone();
+        let unread = unread.clone();
+        let force_update = force_update.clone();
+        Callback::from(move |_| {
+            if let Some(room) = current_room.borrow().as_deref() {
+                chat_notify::set_muted(room, !muted);
+                chat_notify::publish(&unread, &messages);
+                force_update.force_update();
+            }
+        })
+    };
+
+    let (room_messages, history_loading) = props
+        .current_room
+        .borrow()
+        .as_deref()
+        .and_then(|room| {
+            props.messages.borrow().get(room).map(|x| {
+                (
+                    x.messages.iter().cloned().collect::<Vec<MessageContent>>(),
+                    x.loading,
+                )
+            })
+        })
+        .unwrap_or_default();
+    let typing = props
+        .current_room
+        .borrow()
+        .as_deref()
+        .and_then(|room| chat_presence::typing_text(&props.typings, room));
     let current_room = (&*props.current_room.borrow()).clone();
 
     html! {
         <div class="modal is-active">
             <div class="modal-background"></div>
-            <div class="modal-card" style="height:70%;width:60%;">
+            <div class="modal-card chat-dialog">
                 <header class="modal-card-head">
                 <p class="modal-card-title">{title}</p>
+                <div class="field has-addons chat-search">
+                    <p class={classes!("control", "has-icons-left", searching.then_some("is-loading"))}>
+                        <input ref={search_ref} class="input is-small" type="search" placeholder={i18n.t("chat.search")} onkeydown={key_search}/>
+                        <span class="icon is-small is-left"><i class="fa-solid fa-magnifying-glass"></i></span>
+                    </p>
+                    <p class="control">
+                        <button class={classes!("button", "is-small", all_rooms.then_some("is-info"))} title={i18n.t("chat.search_all")} onclick={toggle_all_rooms}>
+                            {i18n.t("chat.all_rooms")}
+                        </button>
+                    </p>
+                </div>
+                <button class="button is-small is-white" title={i18n.t(if muted {"chat.unmute"} else {"chat.mute"})} onclick={toggle_mute}>
+                    <span class="icon"><i class={if muted {"fa-solid fa-bell-slash"} else {"fa-solid fa-bell"}}></i></span>
+                </button>
                 <button class="delete" aria-label="close" onclick={closedialog}></button>
                 </header>
 
                 <section class="modal-card-body">
-                <div class="columns" style="height:100%;">
+                <div class="columns chat-columns">
                 <div class="column is-2">
-                    <div style="height: 100%; overflow: scroll;">
+                    <div class="chat-scroll">
                         <Menu labels = {room_labels} selected_name = {current_room} onselect = {click_room}/>
                     </div>
                 </div>
                 <div class="column is-7">
-                    <div style="height: 70%;">
-                        <textarea ref={ref1} style="height: 100%;" readonly={true} class="textarea has-fixed-size"></textarea>
+                    <div class="chat-timeline">
+                    {
+                        if showing_results {
+                            search_view
+                        } else {
+                            html!{
+                                <MessageTimeline messages={room_messages} loading={history_loading} onreachtop={load_more} onretry={retry} mention={props.session_id.clone()}
+                                    onedit={edit} ondelete={delete} can_moderate={chat_edit::can_moderate()}/>
+                            }
+                        }
+                    }
                     </div>
-                    <div style="margin-top: 0.8em;">
-                        <textarea ref={ref2} class="textarea has-fixed-size" onkeydown={key_send} />
+                    {
+                        if editing.borrow().is_some() {
+                            html!{ <p class="help is-info">{i18n.t("chat.editing")}</p> }
+                        } else {
+                            html!{}
+                        }
+                    }
+                    <p class="typing-indicator">{typing.unwrap_or_default()}</p>
+                    <div class="attachment-staged">
+                    {
+                        staged.borrow().iter().enumerate().map(|(index, x)| {
+                            let remove = {
+                                let staged = staged.clone();
+                                let force_update = force_update.clone();
+                                Callback::from(move |_| {
+                                    staged.borrow_mut().remove(index).release_preview();
+                                    force_update.force_update();
+                                })
+                            };
+                            html!{
+                                <span class="tag is-light is-medium">
+                                    {
+                                        if let Some(preview) = &x.preview {
+                                            html!{ <img class="attachment-thumb" src={preview.clone()} alt={x.name.clone()}/> }
+                                        } else {
+                                            html!{ <span class="icon is-small"><i class="fa-solid fa-file"></i></span> }
+                                        }
+                                    }
+                                    {format!("{} ({})", x.name, chat_attachment::format_size(x.size))}
+                                    <button class="delete is-small" onclick={remove}></button>
+                                </span>
+                            }
+                        }).collect::<Html>()
+                    }
+                    {
+                        if let Some(err) = &*attach_error.borrow() {
+                            html!{ <p class="help is-danger">{err}</p> }
+                        } else {
+                            html!{}
+                        }
+                    }
+                    </div>
+                    <div class="attachment-drop" ondragover={drag_over} ondrop={drop_files}>
+                        <textarea ref={ref2} class="textarea has-fixed-size" onkeydown={key_send} oninput={input_typing} onpaste={paste_files} />
+                        <button class="button is-small is-white attachment-pick" title={i18n.t("chat.attach")} onclick={pick_files}>
+                            <span class="icon"><i class="fa-solid fa-paperclip"></i></span>
+                        </button>
+                        <input ref={file_ref} type="file" multiple=true class="is-hidden" onchange={choose_files}/>
                     </div>
                 </div>
                 <div class="column is-3">
-                    <div style="height: 100%; overflow: scroll;">
+                    <div class="chat-scroll">
                         <Menu labels = {session_labels}/>
                     </div>
                 </div>
                 </div>
                 </section>
             </div>
+            {
+                if deleting.borrow().is_some() {
+                    html!{
+                        <ConfirmForm content={i18n.t("chat.delete_confirm")} onconfirm={confirm_delete} onclose={cancel_delete}/>
+                    }
+                } else {
+                    html!{}
+                }
+            }
         </div>
     }
 }
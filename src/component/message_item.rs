@@ -1,4 +1,4 @@
-use gloo::timers::callback::Timeout;
+use crate::util::i18n::use_translation;
 use uuid::Uuid;
 use yew::prelude::*;
 use yew::Properties;
@@ -15,6 +15,25 @@ pub enum MessageItemType {
     Danger,
 }
 
+impl MessageItemType {
+    // seconds, errors and chat messages stay until closed
+    pub fn default_timeout(&self) -> Option<u32> {
+        match self {
+            MessageItemType::Success => Some(5),
+            MessageItemType::Info => Some(8),
+            MessageItemType::Warning => Some(10),
+            _ => None,
+        }
+    }
+}
+
+// a button on the toast, the toast is closed after the callback
+#[derive(PartialEq, Debug, Clone)]
+pub struct ToastAction {
+    pub label: String,
+    pub callback: Callback<()>,
+}
+
 #[derive(PartialEq, Debug, Clone)]
 pub struct MessageItemValue {
     pub id: u128,
@@ -25,13 +44,33 @@ pub struct MessageItemValue {
     pub r#type: MessageItemType,
     // seconds
     pub timeout: Option<u32>,
+    pub actions: Vec<ToastAction>,
+    pub time: String,
+}
+
+// state of the auto dismiss timer, owned by the toast service
+#[derive(PartialEq, Debug, Clone)]
+pub struct Countdown {
+    // milliseconds
+    pub remaining: u32,
+    pub total: u32,
+    pub paused: bool,
+    pub generation: u32,
 }
 
 #[derive(PartialEq, Properties, Debug)]
 pub struct MessageItemProps {
     pub value: MessageItemValue,
+    #[prop_or_default]
+    pub countdown: Option<Countdown>,
     pub onclose: Callback<u128>,
-    pub onopendialog: Callback<u128>,
+    #[prop_or_default]
+    pub onpause: Callback<u128>,
+    #[prop_or_default]
+    pub onresume: Callback<u128>,
+    // keep the toast until it is closed
+    #[prop_or_default]
+    pub onpersist: Callback<u128>,
 }
 
 impl MessageItemValue {
@@ -51,54 +90,125 @@ impl MessageItemValue {
             from_id: from_id.and_then(|x| Some(x.to_string())),
             from: from.and_then(|x| Some(x.to_string())),
             timeout,
+            actions: vec![],
+            time: chrono::Local::now().format("%H:%M:%S").to_string(),
+        }
+    }
+
+    // timeout by severity
+    pub fn of(r#type: MessageItemType, room: &str, content: &str) -> Self {
+        let timeout = r#type.default_timeout();
+        Self::new(r#type, room, content, timeout, None, None)
+    }
+
+    pub fn with_action(mut self, label: &str, callback: Callback<()>) -> Self {
+        self.actions.push(ToastAction {
+            label: label.to_string(),
+            callback,
+        });
+        self
+    }
+
+    pub fn display_content(&self) -> String {
+        let content = self.content.trim_matches('"');
+        match &self.from {
+            Some(name) => format!("{name}: {content}"),
+            None => content.to_string(),
         }
     }
 }
 
 #[function_component(MessageItem)]
 pub fn message_item(props: &MessageItemProps) -> Html {
+    let i18n = use_translation();
     let value = props.value.clone();
-    let content = value.content.trim_matches('"');
-    let content = match &value.from {
-        Some(name) => format!("{name}: {content}"),
-        None => content.to_string(),
-    };
+    let content = value.display_content();
     let id = value.id;
 
     let class_t = format!("{:?}", &value.r#type).to_lowercase();
-    let cursor_style = if value.r#type == MessageItemType::Primary {
-        "cursor: pointer"
-    } else {
-        ""
-    };
 
     let close = {
         let onclose = props.onclose.clone();
         Callback::from(move |_| onclose.emit(id))
     };
 
-    let open_dialog = {
-        if value.r#type == MessageItemType::Primary {
-            let on_open_dialog = props.onopendialog.clone();
-            Callback::from(move |_| on_open_dialog.emit(id))
-        } else {
-            Callback::from(|_| {})
-        }
+    let enter = {
+        let onpause = props.onpause.clone();
+        Callback::from(move |_| onpause.emit(id))
+    };
+    let leave = {
+        let onresume = props.onresume.clone();
+        Callback::from(move |_| onresume.emit(id))
+    };
+    let persist = {
+        let onpersist = props.onpersist.clone();
+        Callback::from(move |_| onpersist.emit(id))
     };
 
-    if let Some(timeout) = value.timeout {
-        let onclose = props.onclose.clone();
-        Timeout::new(1000 * timeout, move || onclose.emit(id)).forget();
-    }
+    // the bar shrinks from the remaining fraction to zero, a new generation restarts it
+    let countdown = match &props.countdown {
+        Some(countdown) => {
+            let from = countdown.remaining as f64 / countdown.total.max(1) as f64;
+            let style = if countdown.paused {
+                format!("transform: scaleX({from:.3});")
+            } else {
+                format!(
+                    "--countdown-from: {from:.3}; animation-duration: {}ms;",
+                    countdown.remaining
+                )
+            };
+            html! {
+                <div key={countdown.generation.to_string()} class={classes!("toast-countdown", (!countdown.paused).then_some("is-running"))} {style}></div>
+            }
+        }
+        None => html! {},
+    };
 
     html! {
-        <article class={format!{"message is-light is-small is-{}", class_t}}>
+        <article class={format!{"message is-light is-small is-{}", class_t}} onmouseenter={enter} onmouseleave={leave}>
         <div class="message-header">
             <p>{value.room.clone()}</p>
+            {
+                if props.countdown.is_some() {
+                    html!{
+                        <a class="toast-pin" href={String::from("javascript:void(0)")} title={i18n.t("toast.keep")} onclick={persist}>
+                            <i class="fa-solid fa-thumbtack"></i>
+                        </a>
+                    }
+                } else {
+                    html!{}
+                }
+            }
             <button class="delete" aria-label="delete" onclick = {close}></button>
         </div>
-        <div class="message-body"  style={cursor_style} onclick = {open_dialog}>
+        {countdown}
+        <div class="message-body">
            {content}
+           {
+                if !value.actions.is_empty() {
+                    html!{
+                        <div class="buttons are-small toast-actions">
+                        {
+                            value.actions.iter().map(|x| {
+                                let onclick = {
+                                    let callback = x.callback.clone();
+                                    let onclose = props.onclose.clone();
+                                    Callback::from(move |_| {
+                                        callback.emit(());
+                                        onclose.emit(id);
+                                    })
+                                };
+                                html!{
+                                    <button class={format!("button is-{}", class_t)} {onclick}>{&x.label}</button>
+                                }
+                            }).collect::<Html>()
+                        }
+                        </div>
+                    }
+                } else {
+                    html!{}
+                }
+           }
         </div>
         </article>
     }
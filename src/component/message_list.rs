@@ -1,19 +1,26 @@
 #![allow(dead_code)]
 
-use super::message_item::{MessageItem, MessageItemType, MessageItemValue};
-use serde::Deserialize;
+use super::chat_attachment::Attachment;
+use super::chat_delivery::{self, Ack, Delivered, WsWriter};
+use super::chat_edit::{self, Deleted, Edited, DELETED_PRE, EDITED_PRE};
+use super::chat_history::{self, Histories};
+use super::chat_notify::{self, ChatUnreadHandle};
+use super::chat_presence::{
+    self, IdleWatcher, PresenceChange, Presences, Typing, Typings, PRESENCE_PRE, TYPING_PRE,
+};
+use super::toast::{self, use_toast};
+use serde::{Deserialize, Serialize};
 use std::cell::RefCell;
 use std::rc::Rc;
 use yew::prelude::*;
 use yew::Properties;
 
 use super::message_dialog::MessageDialog;
+use crate::util::i18n;
 use crate::util::request;
-use futures::stream::SplitSink;
-use futures::{SinkExt, StreamExt};
-use gloo_net::websocket::{futures::WebSocket, Message};
+use futures::StreamExt;
+use gloo_net::websocket::Message;
 use std::collections::HashMap;
-use std::collections::LinkedList;
 use wasm_bindgen_futures::spawn_local;
 
 const DEFAULT_ROOM: &str = "main";
@@ -23,6 +30,8 @@ const JOIN_ROOM_PRE: &str = "join_room:";
 const QUIT_ROOM_PRE: &str = "quit_room:";
 const UPDATE_NAME_PRE: &str = "update_name:";
 const MESSAGE_PRE: &str = "message:";
+const ACK_PRE: &str = "ack:";
+const DELIVERED_PRE: &str = "delivered:";
 
 #[derive(Deserialize)]
 struct UpdateSession<'a> {
@@ -46,21 +55,17 @@ struct RoomChange<'a> {
 
 #[derive(Clone, Properties)]
 pub struct MessageListProps {
-    #[prop_or_default]
-    pub value: Option<MessageItemValue>,
     #[prop_or_default]
     pub ws: bool,
 }
 
-pub type MessageListValue = LinkedList<MessageItemValue>;
-
 impl PartialEq for MessageListProps {
     fn eq(&self, _: &Self) -> bool {
         false
     }
 }
 
-#[derive(Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 pub struct MessageContent {
     pub id: u128,
     pub room: String,
@@ -69,72 +74,57 @@ pub struct MessageContent {
     pub content: String,
     pub time: String,
     pub is_own: Option<()>,
+    #[serde(default)]
+    pub client_id: Option<String>,
+    #[serde(default)]
+    pub status: Option<DeliveryStatus>,
+    #[serde(default)]
+    pub attachments: Vec<Attachment>,
+    #[serde(default)]
+    pub edited_at: Option<String>,
+    #[serde(default)]
+    pub deleted: bool,
 }
 
-pub fn ok(msg: &str) -> MessageItemValue {
-    MessageItemValue::new(
-        MessageItemType::Success,
-        "Success",
-        msg,
-        Some(5),
-        None,
-        None,
-    )
-}
-
-pub fn warn(msg: &str) -> MessageItemValue {
-    MessageItemValue::new(
-        MessageItemType::Warning,
-        "Warning",
-        msg,
-        Some(10),
-        None,
-        None,
-    )
-}
-
-pub fn info(msg: &str) -> MessageItemValue {
-    MessageItemValue::new(MessageItemType::Info, "Info", msg, Some(8), None, None)
-}
-
-pub fn error(msg: &str) -> MessageItemValue {
-    MessageItemValue::new(MessageItemType::Danger, "Error", msg, None, None, None)
-}
-
-pub fn message(room: &str, from_id: &str, from_name: &str, content: &str) -> MessageItemValue {
-    MessageItemValue::new(
-        MessageItemType::Primary,
-        room,
-        content,
-        None,
-        Some(from_name),
-        Some(from_id),
-    )
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum DeliveryStatus {
+    Pending,
+    Sent,
+    Delivered,
+    Failed,
 }
 
 #[function_component(MessageList)]
 pub fn message_list(props: &MessageListProps) -> Html {
     let force_update = use_force_update();
     let dialog_closed: Rc<RefCell<bool>> = use_mut_ref(|| true);
-    let ws_writer: Rc<RefCell<Option<SplitSink<WebSocket, Message>>>> =
-        use_mut_ref(|| Default::default());
+    let ws_writer: WsWriter = use_mut_ref(|| Default::default());
     let rooms: Rc<RefCell<HashMap<String, HashMap<String, String>>>> =
         use_mut_ref(|| Default::default());
     let session_id: Rc<RefCell<Option<String>>> = use_mut_ref(|| Default::default());
-    let text_messages: Rc<RefCell<HashMap<String, LinkedList<MessageContent>>>> =
-        use_mut_ref(|| Default::default());
+    let text_messages: Histories = use_mut_ref(|| Default::default());
     let current_room: Rc<RefCell<Option<String>>> = use_mut_ref(|| Default::default());
-    let message_list = use_mut_ref(|| MessageListValue::new());
+    let toaster = use_toast();
+    let unread = use_context::<ChatUnreadHandle>();
+    let presences: Presences = use_mut_ref(|| Default::default());
+    let typings: Typings = use_mut_ref(|| Default::default());
+    let idle_watcher: Rc<RefCell<Option<IdleWatcher>>> = use_mut_ref(|| None);
 
-    {
-        let message_list = message_list.clone();
-        let value = props.value.clone();
-        use_memo(value, move |value| {
-            if let Some(v) = value {
-                message_list.borrow_mut().push_back(v.clone());
-            }
-        });
-    }
+    let open_room = {
+        let dialog_closed = dialog_closed.clone();
+        let current_room = current_room.clone();
+        let toaster = toaster.clone();
+        let force_update = force_update.clone();
+        Callback::from(move |room: String| {
+            *dialog_closed.borrow_mut() = false;
+            // only the chat toasts of the opened room are dismissed
+            toaster.close_room(&room);
+            *current_room.borrow_mut() = Some(room);
+            chat_notify::request_permission();
+            force_update.force_update();
+        })
+    };
 
     {
         let force_update = force_update.clone();
@@ -145,22 +135,29 @@ pub fn message_list(props: &MessageListProps) -> Html {
         let self_rooms = rooms.clone();
         let session_id = session_id.clone();
         let session_id_c = session_id.clone();
-        let message_list = message_list.clone();
+        let toaster = toaster.clone();
+        let open_room = open_room.clone();
         let current_room = current_room.clone();
+        let unread = unread.clone();
+        let presences = presences.clone();
+        let typings = typings.clone();
+        let idle_watcher = idle_watcher.clone();
         // depends on (), only effected once
         use_effect_with((), move |_| {
             if ws {
                 let ws = request::open_ws().unwrap();
                 let (writer, mut reader) = ws.split();
-                *ws_writer.borrow_mut() = Some(writer);
-                let w1 = Rc::clone(&ws_writer);
+                // frames are queued through a channel so that sends never hold the writer
+                let (tx, rx) = futures::channel::mpsc::unbounded::<Message>();
+                *ws_writer.borrow_mut() = Some(tx);
                 spawn_local(async move {
-                    if let Some(ss) = &mut *w1.borrow_mut() {
-                        ss.send(Message::Text(String::from("i am back online!")))
-                            .await
-                            .unwrap();
+                    if let Err(err) = rx.map(Ok).forward(writer).await {
+                        log::error!("websocket write error: {:#?}", err);
                     }
                 });
+                chat_delivery::send_frame(&ws_writer, String::from("i am back online!"))
+                    .unwrap_or_else(|e| log::error!("{}", e));
+                *idle_watcher.borrow_mut() = Some(IdleWatcher::new(&ws_writer));
 
                 let mut sid = session_id_c.borrow_mut();
                 if sid.is_none() {
@@ -179,20 +176,141 @@ pub fn message_list(props: &MessageListProps) -> Html {
                                             content.trim_start_matches(MESSAGE_PRE),
                                         )
                                         .unwrap();
-                                        text_messages
+                                        chat_presence::clear_typing(
+                                            &typings,
+                                            &message_content.room,
+                                            &message_content.from_id,
+                                        );
+                                        let is_new = text_messages
                                             .borrow_mut()
                                             .entry(message_content.room.clone())
-                                            .or_insert(Default::default())
-                                            .push_back(message_content.clone());
+                                            .or_default()
+                                            .push_live(message_content.clone());
+                                        chat_history::persist(
+                                            &text_messages,
+                                            &message_content.room,
+                                        );
 
-                                        if *dialog_closed.borrow() {
-                                            message_list.borrow_mut().push_back(message(
-                                                &message_content.room,
-                                                &message_content.from_id,
-                                                &message_content.from_name,
+                                        if is_new && message_content.is_own.is_none() {
+                                            let room = message_content.room.as_str();
+                                            let name =
+                                                session_id.borrow().clone().unwrap_or_default();
+                                            let mentioned = chat_notify::is_mentioned(
                                                 &message_content.content,
-                                            ));
+                                                &name,
+                                            );
+                                            let viewing = !*dialog_closed.borrow()
+                                                && current_room.borrow().as_deref() == Some(room)
+                                                && !chat_notify::is_hidden();
+                                            if !viewing {
+                                                if let Some(history) =
+                                                    text_messages.borrow_mut().get_mut(room)
+                                                {
+                                                    history.unread += 1;
+                                                    history.mentioned |= mentioned;
+                                                }
+                                                chat_notify::publish(&unread, &text_messages);
+                                            }
+                                            // mentions get through a muted room
+                                            if mentioned || !chat_notify::is_muted(room) {
+                                                if *dialog_closed.borrow() {
+                                                    let open_room = open_room.clone();
+                                                    let target = room.to_string();
+                                                    toaster.push(
+                                                        toast::message(
+                                                            room,
+                                                            &message_content.from_id,
+                                                            &message_content.from_name,
+                                                            &message_content.content,
+                                                        )
+                                                        .with_action(
+                                                            &i18n::t("chat.open"),
+                                                            Callback::from(move |_| {
+                                                                open_room.emit(target.clone())
+                                                            }),
+                                                        ),
+                                                    );
+                                                }
+                                                if chat_notify::is_hidden() {
+                                                    chat_notify::notify(
+                                                        room,
+                                                        &message_content.from_name,
+                                                        &message_content.content,
+                                                    );
+                                                }
+                                            }
                                         }
+                                    } else if content.starts_with(PRESENCE_PRE) {
+                                        let change: PresenceChange = match serde_json::from_str(
+                                            content.trim_start_matches(PRESENCE_PRE),
+                                        ) {
+                                            Ok(v) => v,
+                                            Err(err) => {
+                                                log::error!("malformed presence frame: {}", err);
+                                                continue;
+                                            }
+                                        };
+                                        chat_presence::on_presence(&presences, change);
+                                    } else if content.starts_with(TYPING_PRE) {
+                                        let typing: Typing = match serde_json::from_str(
+                                            content.trim_start_matches(TYPING_PRE),
+                                        ) {
+                                            Ok(v) => v,
+                                            Err(err) => {
+                                                log::error!("malformed typing frame: {}", err);
+                                                continue;
+                                            }
+                                        };
+                                        let force_update = force_update.clone();
+                                        chat_presence::on_typing(
+                                            &typings,
+                                            typing,
+                                            Callback::from(move |_| force_update.force_update()),
+                                        );
+                                    } else if content.starts_with(ACK_PRE) {
+                                        let ack: Ack = match serde_json::from_str(
+                                            content.trim_start_matches(ACK_PRE),
+                                        ) {
+                                            Ok(v) => v,
+                                            Err(err) => {
+                                                log::error!("malformed ack frame: {}", err);
+                                                continue;
+                                            }
+                                        };
+                                        chat_delivery::ack(&text_messages, ack);
+                                    } else if content.starts_with(EDITED_PRE) {
+                                        let edited: Edited = match serde_json::from_str(
+                                            content.trim_start_matches(EDITED_PRE),
+                                        ) {
+                                            Ok(v) => v,
+                                            Err(err) => {
+                                                log::error!("malformed edited frame: {}", err);
+                                                continue;
+                                            }
+                                        };
+                                        chat_edit::edited(&text_messages, edited);
+                                    } else if content.starts_with(DELETED_PRE) {
+                                        let deleted: Deleted = match serde_json::from_str(
+                                            content.trim_start_matches(DELETED_PRE),
+                                        ) {
+                                            Ok(v) => v,
+                                            Err(err) => {
+                                                log::error!("malformed deleted frame: {}", err);
+                                                continue;
+                                            }
+                                        };
+                                        chat_edit::deleted(&text_messages, deleted);
+                                    } else if content.starts_with(DELIVERED_PRE) {
+                                        let delivered: Delivered = match serde_json::from_str(
+                                            content.trim_start_matches(DELIVERED_PRE),
+                                        ) {
+                                            Ok(v) => v,
+                                            Err(err) => {
+                                                log::error!("malformed delivered frame: {}", err);
+                                                continue;
+                                            }
+                                        };
+                                        chat_delivery::delivered(&text_messages, delivered);
                                     } else {
                                         if content.starts_with(UPDATE_SESSION_PRE) {
                                             let change: UpdateSession = serde_json::from_str(
@@ -285,54 +403,14 @@ pub fn message_list(props: &MessageListProps) -> Html {
             force_update.force_update()
         })
     };
-    let list = &*message_list.borrow();
 
     html! {
         <>
-        <div class="message-list">
-        {
-            list.iter().map(|x|{
-                let open_dialog = {
-                    let dialog_closed = dialog_closed.clone();
-                    let value = message_list.clone();
-                    let current_room = current_room.clone();
-                    let force_update = force_update.clone();
-                    let item = x.clone();
-                    Callback::from(move |_| {
-                        let item = item.clone();
-                        *dialog_closed.borrow_mut() = false;
-                        *current_room.borrow_mut() = Some(item.room);
-                        value.borrow_mut().clear();
-                        force_update.force_update();
-                    })
-                };
-
-                let close_message_item = {
-                    let value = message_list.clone();
-                    let force_update = force_update.clone();
-                    Callback::from(move |id| {
-                       let index = value
-                            .borrow()
-                            .iter()
-                            .enumerate()
-                            .find(|&(_, v)| v.id == id)
-                            .map(|(index, _)| index);
-                        if let Some(index) = index{
-                            value.borrow_mut().remove(index);
-                            force_update.force_update();
-                        }
-                    })
-                };
-                html!{
-                <MessageItem value = {x.clone()} onclose={close_message_item} onopendialog={open_dialog}/>
-            }}).collect::<Html>()
-        }
-        </div>
         {
             if !*dialog_closed.borrow() {
                 let session_id = session_id.borrow().clone().unwrap();
                 html!{
-                    <MessageDialog session_id={session_id} rooms={rooms.clone()} messages={text_messages.clone()}  ws_writer = {ws_writer.clone()} onclose={on_close} current_room = { current_room }/>
+                    <MessageDialog session_id={session_id} rooms={rooms.clone()} messages={text_messages.clone()} presences={presences.clone()} typings={typings.clone()} ws_writer = {ws_writer.clone()} onclose={on_close} current_room = { current_room }/>
                 }
             }else{
                 html!{}
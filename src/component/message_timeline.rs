@@ -0,0 +1,339 @@
+use crate::component::chat_attachment::{self, Attachment};
+use crate::component::message_list::{DeliveryStatus, MessageContent};
+use crate::util::i18n::{self, use_translation};
+use crate::util::{markdown, time};
+use chrono::{DateTime, Local};
+use std::cell::RefCell;
+use std::rc::Rc;
+use web_sys::HtmlElement;
+use yew::prelude::*;
+use yew::Properties;
+
+// messages from one sender within this interval are shown as one group
+const GROUP_INTERVAL_SECS: i64 = 5 * 60;
+// distance to the bottom that still counts as "at the bottom"
+const BOTTOM_THRESHOLD: i32 = 24;
+
+#[derive(Clone, PartialEq, Properties)]
+pub struct MessageTimelineProps {
+    #[prop_or_default]
+    pub messages: Vec<MessageContent>,
+    #[prop_or_default]
+    pub loading: bool,
+    #[prop_or_default]
+    pub onreachtop: Callback<()>,
+    // client id of a failed message
+    #[prop_or_default]
+    pub onretry: Callback<String>,
+    // name of the current user, "@name" is highlighted
+    #[prop_or_default]
+    pub mention: Option<String>,
+    #[prop_or_default]
+    pub onedit: Callback<MessageContent>,
+    #[prop_or_default]
+    pub ondelete: Callback<MessageContent>,
+    // messages of others can be deleted too
+    #[prop_or_default]
+    pub can_moderate: bool,
+}
+
+fn sender_key(message: &MessageContent) -> String {
+    if message.is_own.is_some() {
+        String::from("")
+    } else {
+        message.from_id.clone()
+    }
+}
+
+struct MessageGroup<'a> {
+    sender: String,
+    is_own: bool,
+    time: Option<DateTime<Local>>,
+    messages: Vec<&'a MessageContent>,
+}
+
+fn group_messages(messages: &[MessageContent]) -> Vec<MessageGroup<'_>> {
+    let mut groups: Vec<MessageGroup> = vec![];
+    let mut last_time: Option<DateTime<Local>> = None;
+    for message in messages {
+        let time = time::parse(&message.time);
+        let same_group = match groups.last() {
+            Some(group) => {
+                group.sender == sender_key(message)
+                    && match (last_time, time) {
+                        (Some(last), Some(now)) => (now - last).num_seconds() < GROUP_INTERVAL_SECS,
+                        _ => true,
+                    }
+            }
+            None => false,
+        };
+        if same_group {
+            groups.last_mut().unwrap().messages.push(message);
+        } else {
+            groups.push(MessageGroup {
+                sender: sender_key(message),
+                is_own: message.is_own.is_some(),
+                time,
+                messages: vec![message],
+            });
+        }
+        if time.is_some() {
+            last_time = time;
+        }
+    }
+    groups
+}
+
+fn render_status(message: &MessageContent, onretry: &Callback<String>) -> Html {
+    match message.status {
+        Some(DeliveryStatus::Pending) => html! {
+            <span class="timeline-status" title={i18n::t("chat.sending")}><i class="fa-regular fa-clock"></i></span>
+        },
+        Some(DeliveryStatus::Sent) => html! {
+            <span class="timeline-status" title={i18n::t("chat.sent")}><i class="fa-solid fa-check"></i></span>
+        },
+        Some(DeliveryStatus::Delivered) => html! {
+            <span class="timeline-status" title={i18n::t("chat.delivered")}><i class="fa-solid fa-check-double"></i></span>
+        },
+        Some(DeliveryStatus::Failed) => {
+            let retry = {
+                let onretry = onretry.clone();
+                let client_id = message.client_id.clone().unwrap_or_default();
+                Callback::from(move |_| onretry.emit(client_id.clone()))
+            };
+            html! {
+                <span class="timeline-status has-text-danger">
+                    <i class="fa-solid fa-circle-exclamation"></i>
+                    {i18n::t("chat.failed")}
+                    <a href={String::from("javascript:void(0)")} onclick={retry}>{i18n::t("chat.retry")}</a>
+                </span>
+            }
+        }
+        None => html! {},
+    }
+}
+
+fn render_actions(message: &MessageContent, props: &MessageTimelineProps) -> Html {
+    if message.id == 0 || message.deleted {
+        return html! {};
+    }
+    let is_own = message.is_own.is_some();
+    let edit = {
+        let onedit = props.onedit.clone();
+        let message = message.clone();
+        Callback::from(move |_| onedit.emit(message.clone()))
+    };
+    let delete = {
+        let ondelete = props.ondelete.clone();
+        let message = message.clone();
+        Callback::from(move |_| ondelete.emit(message.clone()))
+    };
+    html! {
+        <span class="timeline-actions">
+            {
+                if is_own {
+                    html!{
+                        <a href={String::from("javascript:void(0)")} title={i18n::t("chat.edit")} onclick={edit}>
+                            <i class="fa-solid fa-pen"></i>
+                        </a>
+                    }
+                } else {
+                    html!{}
+                }
+            }
+            {
+                if is_own || props.can_moderate {
+                    html!{
+                        <a href={String::from("javascript:void(0)")} title={i18n::t("chat.delete")} onclick={delete}>
+                            <i class="fa-solid fa-trash"></i>
+                        </a>
+                    }
+                } else {
+                    html!{}
+                }
+            }
+        </span>
+    }
+}
+
+fn render_attachment(attachment: &Attachment) -> Html {
+    let progress = match attachment.progress {
+        Some(progress) => html! {
+            <progress class="progress is-small is-info" value={progress.to_string()} max="100"></progress>
+        },
+        None => html! {},
+    };
+    let href = attachment
+        .href()
+        .unwrap_or("javascript:void(0)")
+        .to_string();
+    if attachment.is_image() {
+        let image = match attachment.display_url() {
+            Some(src) => html! {
+                <img class="timeline-image" src={src.to_string()} alt={attachment.name.clone()}/>
+            },
+            None => html! {
+                <span class="icon"><i class="fa-solid fa-image"></i></span>
+            },
+        };
+        html! {
+            <div class="timeline-attachment">
+                <a {href} target="_blank" title={attachment.name.clone()}>
+                    {image}
+                </a>
+                {progress}
+            </div>
+        }
+    } else {
+        html! {
+            <div class="timeline-attachment">
+                <a {href} target="_blank" download={attachment.name.clone()}>
+                    <span class="icon"><i class="fa-solid fa-file"></i></span>
+                    {&attachment.name}
+                </a>
+                <span class="timeline-time">{chat_attachment::format_size(attachment.size)}</span>
+                {progress}
+            </div>
+        }
+    }
+}
+
+#[function_component(MessageTimeline)]
+pub fn message_timeline(props: &MessageTimelineProps) -> Html {
+    let i18n = use_translation();
+    let force_update = use_force_update();
+    let timeline_ref = use_node_ref();
+    // auto scroll is paused once the user scrolls up
+    let stick_to_bottom: Rc<RefCell<bool>> = use_mut_ref(|| true);
+    let last_height: Rc<RefCell<i32>> = use_mut_ref(|| 0);
+    let first_id: Rc<RefCell<Option<u128>>> = use_mut_ref(|| None);
+
+    let onscroll = {
+        let stick_to_bottom = stick_to_bottom.clone();
+        let onreachtop = props.onreachtop.clone();
+        let force_update = force_update.clone();
+        Callback::from(move |e: Event| {
+            let el: HtmlElement = e.target_unchecked_into();
+            let at_bottom =
+                el.scroll_top() + el.client_height() >= el.scroll_height() - BOTTOM_THRESHOLD;
+            if *stick_to_bottom.borrow() != at_bottom {
+                *stick_to_bottom.borrow_mut() = at_bottom;
+                force_update.force_update();
+            }
+            if el.scroll_top() == 0 {
+                onreachtop.emit(());
+            }
+        })
+    };
+
+    let to_bottom = {
+        let timeline_ref = timeline_ref.clone();
+        let stick_to_bottom = stick_to_bottom.clone();
+        let force_update = force_update.clone();
+        Callback::from(move |_| {
+            if let Some(el) = timeline_ref.cast::<HtmlElement>() {
+                el.set_scroll_top(el.scroll_height());
+            }
+            *stick_to_bottom.borrow_mut() = true;
+            force_update.force_update();
+        })
+    };
+
+    {
+        let timeline_ref = timeline_ref.clone();
+        let stick_to_bottom = stick_to_bottom.clone();
+        let last_height = last_height.clone();
+        let first_id = first_id.clone();
+        let current_first_id = props.messages.iter().map(|x| x.id).find(|&id| id != 0);
+        use_effect(move || {
+            if let Some(el) = timeline_ref.cast::<HtmlElement>() {
+                let prepended =
+                    first_id.borrow().is_some() && *first_id.borrow() != current_first_id;
+                if prepended && !*stick_to_bottom.borrow() {
+                    // an older page was loaded, keep the viewport on the same message
+                    el.set_scroll_top(el.scroll_top() + el.scroll_height() - *last_height.borrow());
+                } else if *stick_to_bottom.borrow() {
+                    el.set_scroll_top(el.scroll_height());
+                }
+                *last_height.borrow_mut() = el.scroll_height();
+            }
+            *first_id.borrow_mut() = current_first_id;
+        });
+    }
+
+    let groups = group_messages(&props.messages);
+
+    html! {
+        <div class="message-timeline-container">
+            <div ref={timeline_ref} class="message-timeline" {onscroll}>
+            {
+                if props.loading {
+                    html!{ <progress class="progress is-small is-primary" max="100"></progress> }
+                } else {
+                    html!{}
+                }
+            }
+            {
+                groups.iter().map(|group| {
+                    let from_name = group.messages[0].from_name.clone();
+                    let time = group.time.as_ref().map(time::format_short).unwrap_or_default();
+                    let utc = group.time.as_ref().map(time::format_utc).unwrap_or_default();
+                    html!{
+                        <div class={classes!("timeline-group", group.is_own.then_some("is-own"))}>
+                            <p class="timeline-sender">
+                                <b>{from_name}</b>
+                                <span class="timeline-time" title={utc}>{time}</span>
+                            </p>
+                            {
+                                group.messages.iter().map(|x| html!{
+                                    <>
+                                    {
+                                        if x.deleted {
+                                            html!{
+                                                <div class="timeline-bubble timeline-deleted">{i18n.t("chat.deleted")}</div>
+                                            }
+                                        } else if !x.content.is_empty() {
+                                            html!{
+                                                <div class="timeline-bubble content">
+                                                    { markdown::render(&x.content, props.mention.as_deref()) }
+                                                </div>
+                                            }
+                                        } else {
+                                            html!{}
+                                        }
+                                    }
+                                    { x.attachments.iter().map(render_attachment).collect::<Html>() }
+                                    {
+                                        if let Some(edited_at) = &x.edited_at {
+                                            let title = time::parse(edited_at)
+                                                .map(|t| time::format_utc(&t))
+                                                .unwrap_or_else(|| edited_at.clone());
+                                            html!{ <span class="timeline-edited" {title}>{i18n.t("chat.edited")}</span> }
+                                        } else {
+                                            html!{}
+                                        }
+                                    }
+                                    { render_status(x, &props.onretry) }
+                                    { render_actions(x, props) }
+                                    </>
+                                }).collect::<Html>()
+                            }
+                        </div>
+                    }
+                }).collect::<Html>()
+            }
+            </div>
+            {
+                if !*stick_to_bottom.borrow() {
+                    html!{
+                        <button class="button is-small is-rounded is-primary timeline-to-bottom" onclick={to_bottom}>
+                            <span class="icon is-small"><i class="fa-solid fa-arrow-down"></i></span>
+                        </button>
+                    }
+                } else {
+                    html!{}
+                }
+            }
+        </div>
+    }
+}
@@ -1,7 +1,17 @@
+pub mod chat_attachment;
+pub mod chat_delivery;
+pub mod chat_edit;
+pub mod chat_history;
+pub mod chat_notify;
+pub mod chat_presence;
+pub mod chat_search;
 pub mod message_item;
 pub mod message_list;
+pub mod message_timeline;
 pub mod pager;
 pub mod pager_item;
+pub mod timestamp;
+pub mod toast;
 pub mod welcome;
 pub mod message_dialog;
 pub mod menu;
\ No newline at end of file
@@ -1,4 +1,5 @@
 use super::pager_item::PagerItem;
+use crate::util::i18n::use_translation;
 use serde::Deserialize;
 use yew::{prelude::*, Properties};
 
@@ -93,6 +94,7 @@ pub struct PagerProps {
 
 #[function_component(Pager)]
 pub fn pager(props: &PagerProps) -> Html {
+    let i18n = use_translation();
     let page = use_mut_ref(|| Page::default());
     {
         let page = page.clone();
@@ -170,17 +172,6 @@ pub fn pager(props: &PagerProps) -> Html {
         "pagination-link"
     };
 
-    let pre_style = if page.borrow().is_start() {
-        "pointer-events: none;"
-    } else {
-        ""
-    };
-    let next_style = if page.borrow().is_end() {
-        "pointer-events: none;"
-    } else {
-        ""
-    };
-
     html! {
         <nav class="pagination is-small is-rounded is-right ">
         <ul class="pagination-list">
@@ -191,15 +182,15 @@ pub fn pager(props: &PagerProps) -> Html {
                             (0..=3).map(|i|{
                                 let size = DEFAULT_PAGE_SIZE<<i;
                                 html!{
-                                    <option selected={page.borrow().size==size} value={size.to_string()}>{format!("size: {}",size)}</option>
+                                    <option selected={page.borrow().size==size} value={size.to_string()}>{i18n.t_with("pager.size", &[("size", &size)])}</option>
                                 }
                             }).collect::<Html>()
                         }
                     </select>
                 </div>
             </li>
-            <li><a href={format!("javascript:void(0)")} class={pre_class} style={pre_style} onclick = {to_start}>{"<<"}</a></li>
-            <li><a href={format!("javascript:void(0)")} class={pre_class} style={pre_style} onclick = {to_pre}>{"<"}</a></li>
+            <li><a href={format!("javascript:void(0)")} class={pre_class} onclick = {to_start}>{"<<"}</a></li>
+            <li><a href={format!("javascript:void(0)")} class={pre_class} onclick = {to_pre}>{"<"}</a></li>
             {
                 (page.borrow().page_start()..= page.borrow().page_end()).map(|x|{
                     html!{
@@ -208,9 +199,9 @@ pub fn pager(props: &PagerProps) -> Html {
                 }).collect::<Html>()
             }
             // <li><a class="pagination-link is-current" aria-label="Page 46" aria-current="page">{46}</a></li
-            <li><a href={format!("javascript:void(0)")} class={next_class} style={next_style} onclick = {to_next}>{">"}</a></li>
-            <li><a href={format!("javascript:void(0)")} class={next_class} style={next_style} onclick = {to_end}>{">>"}</a></li>
-            <li>{"-- total pages:"}<b>{page.borrow().page_total()}</b>{" total records: "}<b>{page.borrow().total}</b>{" --"}</li>
+            <li><a href={format!("javascript:void(0)")} class={next_class} onclick = {to_next}>{">"}</a></li>
+            <li><a href={format!("javascript:void(0)")} class={next_class} onclick = {to_end}>{">>"}</a></li>
+            <li>{"-- "}{i18n.t_plural("pager.pages", page.borrow().page_total() as i64)}{", "}{i18n.t_plural("pager.records", page.borrow().total as i64)}{" --"}</li>
         </ul>
         </nav>
     }
@@ -0,0 +1,50 @@
+use crate::util::i18n::use_translation;
+use crate::util::time;
+use gloo::timers::callback::Interval;
+use yew::prelude::*;
+use yew::Properties;
+
+// relative texts like "3 minutes ago" are refreshed this often
+const REFRESH_MILLIS: u32 = 60 * 1000;
+
+#[derive(Clone, PartialEq, Properties)]
+pub struct TimestampProps {
+    // epoch seconds or milliseconds
+    pub value: i64,
+    #[prop_or(true)]
+    pub relative: bool,
+}
+
+// local time or "n hours ago", absolute utc time as tooltip
+#[function_component(Timestamp)]
+pub fn timestamp(props: &TimestampProps) -> Html {
+    // renders again when the locale is switched
+    use_translation();
+    let force_update = use_force_update();
+
+    use_effect_with(props.relative, move |relative| {
+        let interval =
+            relative.then(|| Interval::new(REFRESH_MILLIS, move || force_update.force_update()));
+        move || drop(interval)
+    });
+
+    let time = match time::from_epoch(props.value) {
+        Some(v) => v,
+        None => return html! {},
+    };
+    let text = if props.relative {
+        time::format_relative(&time)
+    } else {
+        time::format_local(&time)
+    };
+    // the tooltip shows the local time when the text is relative
+    let title = if props.relative {
+        format!("{}\n{}", time::format_local(&time), time::format_utc(&time))
+    } else {
+        time::format_utc(&time)
+    };
+
+    html! {
+        <time datetime={time.to_rfc3339()} {title}>{text}</time>
+    }
+}
@@ -0,0 +1,354 @@
+use super::message_item::{Countdown, MessageItem, MessageItemType, MessageItemValue};
+use crate::util::i18n::{self, use_translation};
+use gloo::timers::callback::Timeout;
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::rc::Rc;
+use yew::prelude::*;
+
+// older toasts are dropped when more are shown at once
+const MAX_VISIBLE: usize = 5;
+const HISTORY_SIZE: usize = 50;
+// a toast left by the pointer stays at least this long
+const MIN_REMAINING_MILLIS: u32 = 3000;
+
+pub enum ToastCmd {
+    Push(MessageItemValue),
+    Close(u128),
+    // chat toasts of a room, once the room is opened
+    CloseRoom(String),
+    ClearHistory,
+}
+
+#[derive(Default, Clone, PartialEq)]
+pub struct ToastState {
+    pub visible: Vec<MessageItemValue>,
+    pub history: VecDeque<MessageItemValue>,
+}
+
+impl Reducible for ToastState {
+    type Action = ToastCmd;
+
+    fn reduce(self: Rc<Self>, action: Self::Action) -> Rc<Self> {
+        let mut state = (*self).clone();
+        match action {
+            ToastCmd::Push(value) => {
+                state.history.push_front(value.clone());
+                state.history.truncate(HISTORY_SIZE);
+                state.visible.push(value);
+                let overflow = state.visible.len().saturating_sub(MAX_VISIBLE);
+                state.visible.drain(..overflow);
+            }
+            ToastCmd::Close(id) => state.visible.retain(|x| x.id != id),
+            ToastCmd::CloseRoom(room) => state
+                .visible
+                .retain(|x| x.r#type != MessageItemType::Primary || x.room != room),
+            ToastCmd::ClearHistory => state.history.clear(),
+        }
+        state.into()
+    }
+}
+
+fn now() -> i64 {
+    chrono::Utc::now().timestamp_millis()
+}
+
+struct ToastTimer {
+    // None while paused
+    timeout: Option<Timeout>,
+    total: u32,
+    // milliseconds left at started_at
+    remaining: u32,
+    started_at: i64,
+    // changes whenever the countdown bar has to restart
+    generation: u32,
+}
+
+impl ToastTimer {
+    fn left(&self) -> u32 {
+        match self.timeout {
+            Some(_) => self
+                .remaining
+                .saturating_sub((now() - self.started_at).max(0) as u32),
+            None => self.remaining,
+        }
+    }
+}
+
+// auto dismiss timers live here instead of in MessageItem, so that
+// closing or unmounting a toast always cancels its timer
+#[derive(Default)]
+struct ToastTimers {
+    timers: HashMap<u128, ToastTimer>,
+    // shown until closed by the user
+    persisted: HashSet<u128>,
+    refresh: Option<Callback<()>>,
+}
+
+// cheap to clone, can be moved into async tasks
+#[derive(Clone)]
+pub struct Toaster {
+    dispatcher: UseReducerDispatcher<ToastState>,
+    timers: Rc<RefCell<ToastTimers>>,
+}
+
+impl PartialEq for Toaster {
+    fn eq(&self, other: &Self) -> bool {
+        self.dispatcher == other.dispatcher && Rc::ptr_eq(&self.timers, &other.timers)
+    }
+}
+
+impl Toaster {
+    pub fn push(&self, value: MessageItemValue) {
+        self.dispatcher.dispatch(ToastCmd::Push(value));
+    }
+
+    pub fn close(&self, id: u128) {
+        self.dispatcher.dispatch(ToastCmd::Close(id));
+    }
+
+    pub fn close_room(&self, room: &str) {
+        self.dispatcher
+            .dispatch(ToastCmd::CloseRoom(room.to_string()));
+    }
+
+    fn refresh(&self) {
+        let refresh = self.timers.borrow().refresh.clone();
+        if let Some(refresh) = refresh {
+            refresh.emit(());
+        }
+    }
+
+    fn start(&self, id: u128, timer: &mut ToastTimer) {
+        let dispatcher = self.dispatcher.clone();
+        timer.started_at = now();
+        timer.generation += 1;
+        timer.timeout = Some(Timeout::new(timer.remaining, move || {
+            dispatcher.dispatch(ToastCmd::Close(id))
+        }));
+    }
+
+    // starts timers of new toasts and drops, i.e. cancels, those of closed ones
+    fn sync(&self, visible: &[MessageItemValue]) {
+        let mut borrow = self.timers.borrow_mut();
+        let ids = visible.iter().map(|x| x.id).collect::<HashSet<u128>>();
+        borrow.timers.retain(|id, _| ids.contains(id));
+        borrow.persisted.retain(|id| ids.contains(id));
+        for value in visible {
+            let timeout = match value.timeout {
+                Some(timeout) => 1000 * timeout,
+                None => continue,
+            };
+            if borrow.timers.contains_key(&value.id) || borrow.persisted.contains(&value.id) {
+                continue;
+            }
+            let mut timer = ToastTimer {
+                timeout: None,
+                total: timeout,
+                remaining: timeout,
+                started_at: now(),
+                generation: 0,
+            };
+            self.start(value.id, &mut timer);
+            borrow.timers.insert(value.id, timer);
+        }
+    }
+
+    fn countdown(&self, id: u128) -> Option<Countdown> {
+        self.timers.borrow().timers.get(&id).map(|x| Countdown {
+            remaining: x.left(),
+            total: x.total,
+            paused: x.timeout.is_none(),
+            generation: x.generation,
+        })
+    }
+
+    pub fn pause(&self, id: u128) {
+        if let Some(timer) = self.timers.borrow_mut().timers.get_mut(&id) {
+            if timer.timeout.is_some() {
+                timer.remaining = timer.left();
+                timer.timeout = None;
+            }
+        }
+        self.refresh();
+    }
+
+    pub fn resume(&self, id: u128) {
+        if let Some(timer) = self.timers.borrow_mut().timers.get_mut(&id) {
+            if timer.timeout.is_none() {
+                timer.remaining = timer.remaining.max(MIN_REMAINING_MILLIS);
+                timer.total = timer.total.max(timer.remaining);
+                self.start(id, timer);
+            }
+        }
+        self.refresh();
+    }
+
+    // the toast is kept until closed
+    pub fn persist(&self, id: u128) {
+        {
+            let mut borrow = self.timers.borrow_mut();
+            borrow.timers.remove(&id);
+            borrow.persisted.insert(id);
+        }
+        self.refresh();
+    }
+
+    pub fn ok(&self, msg: &str) {
+        self.push(ok(msg));
+    }
+
+    pub fn warn(&self, msg: &str) {
+        self.push(warn(msg));
+    }
+
+    pub fn error(&self, msg: &str) {
+        self.push(error(msg));
+    }
+}
+
+pub fn ok(msg: &str) -> MessageItemValue {
+    MessageItemValue::of(MessageItemType::Success, &i18n::t("toast.success"), msg)
+}
+
+pub fn warn(msg: &str) -> MessageItemValue {
+    MessageItemValue::of(MessageItemType::Warning, &i18n::t("toast.warning"), msg)
+}
+
+pub fn error(msg: &str) -> MessageItemValue {
+    MessageItemValue::of(MessageItemType::Danger, &i18n::t("toast.error"), msg)
+}
+
+pub fn message(room: &str, from_id: &str, from_name: &str, content: &str) -> MessageItemValue {
+    MessageItemValue::new(
+        MessageItemType::Primary,
+        room,
+        content,
+        None,
+        Some(from_name),
+        Some(from_id),
+    )
+}
+
+#[hook]
+pub fn use_toast() -> Toaster {
+    use_context::<Toaster>().expect("use_toast is called outside of ToastProvider")
+}
+
+#[derive(PartialEq, Properties)]
+pub struct ToastProviderProps {
+    #[prop_or_default]
+    pub children: Html,
+}
+
+#[function_component(ToastProvider)]
+pub fn toast_provider(props: &ToastProviderProps) -> Html {
+    let state = use_reducer(ToastState::default);
+    let timers: Rc<RefCell<ToastTimers>> = use_mut_ref(Default::default);
+    let force_update = use_force_update();
+    let toaster = Toaster {
+        dispatcher: state.dispatcher(),
+        timers: timers.clone(),
+    };
+    let drawer_open = use_state(|| false);
+    let i18n = use_translation();
+
+    // pending timers are cancelled on unmount
+    use_effect_with((), move |_| {
+        timers.borrow_mut().refresh = Some(Callback::from(move |_| force_update.force_update()));
+        move || {
+            let mut borrow = timers.borrow_mut();
+            borrow.timers.clear();
+            borrow.refresh = None;
+        }
+    });
+    // timers start after the toasts are rendered, the countdowns are
+    // rendered once more with them
+    {
+        let toaster = toaster.clone();
+        use_effect_with(state.visible.clone(), move |visible| {
+            toaster.sync(visible);
+            toaster.refresh();
+        });
+    }
+
+    let close = {
+        let toaster = toaster.clone();
+        Callback::from(move |id| toaster.close(id))
+    };
+    let pause = {
+        let toaster = toaster.clone();
+        Callback::from(move |id| toaster.pause(id))
+    };
+    let resume = {
+        let toaster = toaster.clone();
+        Callback::from(move |id| toaster.resume(id))
+    };
+    let persist = {
+        let toaster = toaster.clone();
+        Callback::from(move |id| toaster.persist(id))
+    };
+
+    let toggle_drawer = {
+        let drawer_open = drawer_open.clone();
+        Callback::from(move |_| drawer_open.set(!*drawer_open))
+    };
+
+    let clear_history = {
+        let dispatcher = state.dispatcher();
+        Callback::from(move |_| dispatcher.dispatch(ToastCmd::ClearHistory))
+    };
+
+    html! {
+        <ContextProvider<Toaster> context={toaster}>
+            { props.children.clone() }
+            <div class="message-list">
+            {
+                state.visible.iter().map(|x| html!{
+                    <MessageItem key={x.id.to_string()} value={x.clone()} countdown={toaster.countdown(x.id)}
+                        onclose={close.clone()} onpause={pause.clone()} onresume={resume.clone()} onpersist={persist.clone()}/>
+                }).collect::<Html>()
+            }
+            </div>
+            {
+                if !state.history.is_empty() {
+                    html!{
+                        <button class="button is-small is-rounded toast-history-toggle" title={i18n.t("toast.notifications")} onclick={toggle_drawer.clone()}>
+                            <span class="icon is-small"><i class="fa-solid fa-clock-rotate-left"></i></span>
+                            <span>{state.history.len()}</span>
+                        </button>
+                    }
+                } else {
+                    html!{}
+                }
+            }
+            {
+                if *drawer_open {
+                    html!{
+                        <div class="toast-history">
+                            <p class="toast-history-head">
+                                <b>{i18n.t("toast.notifications")}</b>
+                                <a href={String::from("javascript:void(0)")} onclick={clear_history}>{i18n.t("toast.clear")}</a>
+                                <button class="delete" aria-label="close" onclick={toggle_drawer}></button>
+                            </p>
+                            {
+                                state.history.iter().map(|x| {
+                                    let class_t = format!("{:?}", &x.r#type).to_lowercase();
+                                    html!{
+                                        <div class={format!("toast-history-item has-text-{}", class_t)}>
+                                            <span class="timeline-time">{&x.time}</span>
+                                            <b>{&x.room}</b>
+                                            <p>{x.display_content()}</p>
+                                        </div>
+                                    }
+                                }).collect::<Html>()
+                            }
+                        </div>
+                    }
+                } else {
+                    html!{}
+                }
+            }
+        </ContextProvider<Toaster>>
+    }
+}
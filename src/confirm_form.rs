@@ -1,4 +1,5 @@
-use crate::util::common;
+use crate::util::i18n::use_translation;
+use crate::util::sanitize;
 use yew::prelude::*;
 use yew::Properties;
 
@@ -15,6 +16,7 @@ pub struct ConfirmFormProps {
 
 #[function_component(ConfirmForm)]
 pub fn confirm_form(props: &ConfirmFormProps) -> Html {
+    let i18n = use_translation();
     let close = {
         let onclose = props.onclose.clone();
         Callback::from(move |_| {
@@ -34,17 +36,17 @@ pub fn confirm_form(props: &ConfirmFormProps) -> Html {
             <div class="modal-background"></div>
             <div class="modal-card">
                 <header class="modal-card-head">
-                <p class="modal-card-title">{"Confirm"}</p>
+                <p class="modal-card-title">{i18n.t("common.confirm")}</p>
                 <button class="delete" aria-label="close" onclick={close.clone()}></button>
                 </header>
                 <section class="modal-card-body">
                     {
-                        common::create_html("div",&props.content)
+                        sanitize::render("div", &props.content)
                     }
                 </section>
                 <footer class="modal-card-foot">
-                <button class="button is-danger"  onclick={onconfirm}>{"Confirm"}</button>
-                <button class="button" onclick={close}>{"Cancel"}</button>
+                <button class="button is-danger"  onclick={onconfirm}>{i18n.t("common.confirm")}</button>
+                <button class="button" onclick={close}>{i18n.t("common.cancel")}</button>
                 </footer>
             </div>
         </div>
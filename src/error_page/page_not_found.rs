@@ -1,16 +1,18 @@
+use crate::util::i18n::use_translation;
 use yew::prelude::*;
 
 #[function_component(PageNotFound)]
 pub fn page_not_found() -> Html {
+    let i18n = use_translation();
     html! {
         <section class="hero is-bold is-large">
             <div class="hero-body">
                 <div class="container">
                     <h1 class="title">
-                        { "Page not found" }
+                        { i18n.t("error.page_not_found") }
                     </h1>
                     <h2 class="subtitle">
-                        { "Page does not seem to exist" }
+                        { i18n.t("error.page_not_found.detail") }
                     </h2>
                 </div>
             </div>
@@ -1,3 +1,4 @@
+use crate::util::i18n::use_translation;
 use yew::prelude::*;
 
 #[derive(PartialEq, Properties)]
@@ -7,58 +8,60 @@ pub struct RequestProps {
 
 #[function_component(RequestError)]
 pub fn request_error(props: &RequestProps) -> Html {
+    let i18n = use_translation();
+    let lang = i18n.locale.code();
     match props.status {
         401 => html! {
-        <html lang="en">
+        <html {lang}>
         <head>
             <meta charset="utf-8"/>
-            <title>{"401 Unauthorized"}</title>
+            <title>{i18n.t("error.401.title")}</title>
         </head>
         <body align="center">
             <div role="main" align="center">
-                <h1>{"401: Unauthorized"}</h1>
-                <p>{"The request requires user authentication."}</p>
+                <h1>{i18n.t("error.401.title")}</h1>
+                <p>{i18n.t("error.401.detail")}</p>
                 // <p>{"uri: "}<b></b></p>
                 <hr/>
             </div>
             <div role="contentinfo" align="center">
-                <span style="margin-top:15px;"><a href="/login">{"Go to login"}</a></span>
+                <span style="margin-top:15px;"><a href="/login">{i18n.t("error.go_login")}</a></span>
             </div>
         </body>
         </html>
             },
         404 => html! {
-        <html lang="en">
+        <html {lang}>
         <head>
             <meta charset="utf-8"/>
-            <title>{"401 Unauthorized"}</title>
+            <title>{i18n.t("error.404.title")}</title>
         </head>
         <body align="center">
             <div role="main" align="center">
-                <h1>{"404: Not Found"}</h1>
-                <p>{"The requested resource could not be found."}</p>
+                <h1>{i18n.t("error.404.title")}</h1>
+                <p>{i18n.t("error.404.detail")}</p>
                 // <p>{"uri: "}<b></b></p>
                 <hr/>
             </div>
             <div role="contentinfo" align="center">
-                <span style="margin-top:15px;"><a href="/login">{"Go to login"}</a></span>
+                <span style="margin-top:15px;"><a href="/login">{i18n.t("error.go_login")}</a></span>
             </div>
         </body>
         </html>
             },
         _ => html! {
-        <html lang="en">
+        <html {lang}>
         <head>
             <meta charset="utf-8"/>
-            <title>{"request api error"}</title>
+            <title>{i18n.t("error.request")}</title>
         </head>
         <body align="center">
             <div role="main" align="center">
-                <h1>{"request api error"}</h1>
+                <h1>{i18n.t("error.request")}</h1>
                 <hr/>
             </div>
             <div role="contentinfo" align="center">
-                <span style="margin-top:15px;"><a href="/login">{"Go to login"}</a></span>
+                <span style="margin-top:15px;"><a href="/login">{i18n.t("error.go_login")}</a></span>
             </div>
         </body>
         </html>
@@ -1,5 +1,12 @@
+use crate::component::chat_notify::ChatUnreadHandle;
+use crate::component::timestamp::Timestamp;
 use crate::layout::navbar::Navbar;
-use crate::util::common;
+use crate::util::common::{self, CurrentUserHandle};
+use crate::util::i18n::{use_translation, Locale};
+use crate::util::sanitize;
+use crate::util::session;
+use crate::util::theme::{use_theme, ThemeMode};
+use wasm_bindgen_futures::spawn_local;
 use yew::prelude::*;
 
 #[derive(Properties, PartialEq)]
@@ -12,16 +19,22 @@ pub struct HeaderProps {
 
 #[function_component(Header)]
 pub fn header(props: &HeaderProps) -> Html {
-    let mut user = None;
-    match common::get_current_user() {
-        Ok(v) => user = Some(v),
-        Err(err) => {
-            log::warn!("get current user error: {}", err);
-            // common::redirect("/401");
-            common::redirect("/login");
-        }
-    };
+    let current_user = use_context::<CurrentUserHandle>();
+    let mut user = current_user.and_then(|x| (*x).clone());
+    if user.is_none() {
+        match common::get_current_user() {
+            Ok(v) => user = Some(v),
+            Err(err) => {
+                log::warn!("get current user error: {}", err);
+                // common::redirect("/401");
+                common::redirect("/login");
+            }
+        };
+    }
     let user = user.unwrap();
+    let chat_unread = use_context::<ChatUnreadHandle>();
+    let i18n = use_translation();
+    let theme = use_theme();
     let navbar_active = use_state(|| false);
     let navbar_active_class = if *navbar_active { "is-active" } else { "" };
     let toggle_navbar_active = {
@@ -32,10 +45,10 @@ pub fn header(props: &HeaderProps) -> Html {
     };
     let logout = {
         Callback::from(move |_| {
-            common::delete_current_user().unwrap_or_else(|x| {
-                log::error!("{:?}", x);
+            spawn_local(async {
+                session::logout().await;
+                common::redirect("/login");
             });
-            common::redirect("/login");
         })
     };
     html! {
@@ -56,21 +69,102 @@ pub fn header(props: &HeaderProps) -> Html {
                 <div id="navbarBasicExample" class={format!("navbar-menu {navbar_active_class}")}>
                     <Navbar selected_navbar_name={props.selected_navbar_name.clone()} selected_navbar_parent_name={props.selected_navbar_parent_name.clone()}/>
                     <div class="navbar-end">
+                        {
+                            match chat_unread.as_deref() {
+                                Some(unread) if unread.total > 0 => html!{
+                                    <div class="navbar-item" title={i18n.t("header.unread")}>
+                                        <span class="icon"><i class="fa-solid fa-comments"></i></span>
+                                        <span class={classes!("tag", "is-rounded", if unread.mentioned {"is-danger"} else {"is-info"})}>{unread.total}</span>
+                                    </div>
+                                },
+                                _ => html!{},
+                            }
+                        }
+                        <div class="navbar-item has-dropdown is-hoverable">
+                            <a href={String::from("javascript:void(0)")} class="navbar-link" title={i18n.t("header.theme")}>
+                                <span class="icon"><i class={theme.mode.icon()}></i></span>
+                            </a>
+                            <div class="navbar-dropdown is-right">
+                            {
+                                ThemeMode::ALL.iter().map(|&mode| {
+                                    let active = mode == theme.mode;
+                                    let onclick = {
+                                        let theme = theme.clone();
+                                        Callback::from(move |_| theme.set_mode(mode))
+                                    };
+                                    html!{
+                                        <a href={String::from("javascript:void(0)")} class={classes!("navbar-item", active.then_some("is-active"))} {onclick}>
+                                            <span class="icon"><i class={mode.icon()}></i></span>
+                                            <span>{i18n.t(mode.label_key())}</span>
+                                        </a>
+                                    }
+                                }).collect::<Html>()
+                            }
+                            </div>
+                        </div>
                         <div class="navbar-item has-dropdown is-hoverable">
-                            <a href={String::from("javascript:void(0)")} class="navbar-link" style="color:#000000">
-                                { user.name.unwrap_or("unnamed".to_string())}
+                            <a href={String::from("javascript:void(0)")} class="navbar-link" title={i18n.t("header.language")}>
+                                <span class="icon"><i class="fa-solid fa-language"></i></span>
+                                <span>{i18n.locale.label()}</span>
+                            </a>
+                            <div class="navbar-dropdown is-right">
+                            {
+                                Locale::ALL.iter().map(|&locale| {
+                                    let active = locale == i18n.locale;
+                                    let onclick = {
+                                        let i18n = i18n.clone();
+                                        Callback::from(move |_| i18n.set_locale(locale))
+                                    };
+                                    html!{
+                                        <a href={String::from("javascript:void(0)")} class={classes!("navbar-item", active.then_some("is-active"))} {onclick}>
+                                            {locale.label()}
+                                        </a>
+                                    }
+                                }).collect::<Html>()
+                            }
+                            </div>
+                        </div>
+                        <div class="navbar-item has-dropdown is-hoverable">
+                            <a href="/main/profile" class="navbar-link">
+                                {
+                                    match user.avatar.as_deref().filter(|x| sanitize::is_safe_url(x)) {
+                                        Some(avatar) => html!{ <img class="navbar-avatar" src={avatar.to_string()} alt=""/> },
+                                        None => html!{ <span class="icon"><i class="fa-solid fa-circle-user"></i></span> },
+                                    }
+                                }
+                                <span>{ user.name.unwrap_or(i18n.t("common.unnamed"))}</span>
                             </a>
 
                             <div class="navbar-dropdown is-right">
-                            <a href={String::from("javascript:void(0)")} class="navbar-item">
+                            <span class="navbar-item has-text-grey">
                                 {user.r#type}
-                            </a>
-                            <a href={String::from("javascript:void(0)")} class="navbar-item">
+                            </span>
+                            <span class="navbar-item has-text-grey">
                                 {user.email}
+                            </span>
+                            <a href="/main/profile" class="navbar-item">
+                                <span class="icon"><i class="fa-solid fa-id-card"></i></span>
+                                <span>{i18n.t("header.profile")}</span>
                             </a>
+                            {
+                                match user.laston {
+                                    Some(laston) => html!{
+                                        <span class="navbar-item has-text-grey">
+                                            {i18n.t("header.laston")}<Timestamp value={laston}/>
+                                        </span>
+                                    },
+                                    None => html!{},
+                                }
+                            }
                             <hr class="navbar-divider"/>
+                            <a href="/main/change_pwd" class="navbar-item">
+                                {i18n.t("header.change_pwd")}
+                            </a>
+                            <a href="/main/sessions" class="navbar-item">
+                                {i18n.t("header.sessions")}
+                            </a>
                             <a href={String::from("javascript:void(0)")} onclick={logout} class="navbar-item">
-                                {"Logout"}
+                                {i18n.t("header.logout")}
                             </a>
                             </div>
                         </div>
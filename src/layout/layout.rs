@@ -1,6 +1,9 @@
+use crate::component::chat_notify::{ChatUnread, ChatUnreadHandle};
 use crate::component::menu::{Menu, MenuLabel};
+use crate::component::toast::ToastProvider;
 use crate::layout::header::Header;
-use crate::util::common;
+use crate::util::common::{self, CurrentUserHandle};
+use crate::util::session;
 use yew::prelude::*;
 use yew::virtual_dom::VNode;
 
@@ -33,6 +36,24 @@ pub fn gen_items<'a>() -> Vec<Item<'a>> {
             navbar_parent_name: Some("Modules"),
             left_menu_name: Some("Role"),
         },
+        Item {
+            path: "/main/profile",
+            navbar_name: None,
+            navbar_parent_name: None,
+            left_menu_name: None,
+        },
+        Item {
+            path: "/main/change_pwd",
+            navbar_name: None,
+            navbar_parent_name: None,
+            left_menu_name: None,
+        },
+        Item {
+            path: "/main/sessions",
+            navbar_name: None,
+            navbar_parent_name: None,
+            left_menu_name: None,
+        },
         Item {
             path: "/",
             navbar_name: Some("Welcome"),
@@ -61,6 +82,9 @@ pub fn body(props: &BodyProps) -> Html {
     let (selected_navbar_name, selected_navbar_parent_name, selected_name) =
         get_selected_navbar_and_menu();
     let labels = props.menus.clone();
+    let chat_unread = use_state(ChatUnread::default);
+    let current_user = use_state(|| common::get_current_user().ok());
+    session::use_session_sync(Some(current_user.clone()));
     let on_select_menu = Callback::from(move |name: String| {
         if let Some(item) = gen_items()
             .iter()
@@ -70,7 +94,9 @@ pub fn body(props: &BodyProps) -> Html {
         }
     });
     html! {
-        <>
+        <ContextProvider<ChatUnreadHandle> context={chat_unread}>
+        <ContextProvider<CurrentUserHandle> context={current_user}>
+        <ToastProvider>
              <Header selected_navbar_name={selected_navbar_name} selected_navbar_parent_name={selected_navbar_parent_name} />
              {
                 if !props.menus.is_empty() {
@@ -88,6 +114,8 @@ pub fn body(props: &BodyProps) -> Html {
                     props.content.clone()
                 }
              }
-        </>
+        </ToastProvider>
+        </ContextProvider<CurrentUserHandle>>
+        </ContextProvider<ChatUnreadHandle>>
     }
 }
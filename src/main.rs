@@ -1,27 +1,35 @@
 #![feature(linked_list_remove)]
-// mod component;
-// mod confirm_form;
+mod change_pwd;
+mod component;
+mod confirm_form;
 mod error_page;
 // mod forget_pwd;
-// mod layout;
+mod layout;
 mod login;
+mod profile;
 mod register;
-// mod role_list;
+mod role_list;
+mod sessions;
 // mod user_form;
 // mod user_list;
 mod util;
 
-// use component::menu::{MenuLabel, MenuNode};
+use change_pwd::ChangePwd;
+use component::menu::{MenuLabel, MenuNode};
 // use component::welcome::Welcome;
 use error_page::{page_not_found::PageNotFound, request_error::RequestError};
 // use forget_pwd::ForgetPwd;
-// use layout::layout::Layout;
-use login::Login;
+use layout::layout::Layout;
+use login::{Login, SsoCallback};
+use profile::Profile;
 use register::Register;
-// use role_list::RoleList;
+use role_list::RoleList;
+use sessions::Sessions;
 // use user_list::UserList;
+use util::i18n::I18nProvider;
+use util::theme::ThemeProvider;
 use yew::prelude::*;
-// use yew::virtual_dom::VNode;
+use yew::virtual_dom::VNode;
 use yew_router::prelude::*;
 use yew_router::BrowserRouter;
 
@@ -34,6 +42,12 @@ enum RouteBody {
     User, // Hello,
     #[at("/main/role")]
     Role, // Hello,
+    #[at("/main/change_pwd")]
+    ChangePwd,
+    #[at("/main/profile")]
+    Profile,
+    #[at("/main/sessions")]
+    Sessions,
 }
 
 #[derive(Clone, Routable, PartialEq)]
@@ -49,34 +63,55 @@ enum Route {
     // ForgetPwd,
     #[at("/register")]
     Register,
-    // #[at("/main/:?")]
-    // Body,
+    #[at("/sso/callback")]
+    SsoCallback,
+    #[at("/main/:?")]
+    Body,
     #[at("/401")]
     Unauthorized,
     #[at("/404")]
     NotFound,
 }
 
-// fn switch_body(route: RouteBody) -> VNode {
-//     match route {
-//         RouteBody::PageNotFound => {
-//             html! {
-//                 <PageNotFound />
-//             }
-//         }
-//         RouteBody::User => {
-//             html! {
-//                 <UserList />
-//             }
-//         }
+fn switch_body(route: RouteBody) -> VNode {
+    match route {
+        // UserList still needs the user_cli client
+        RouteBody::PageNotFound | RouteBody::User => {
+            html! {
+                <PageNotFound />
+            }
+        }
+        // RouteBody::User => {
+        //     html! {
+        //         <UserList />
+        //     }
+        // }
+
+        RouteBody::Role => {
+            html! {
+                <RoleList />
+            }
+        }
+
+        RouteBody::ChangePwd => {
+            html! {
+                <ChangePwd />
+            }
+        }
+
+        RouteBody::Profile => {
+            html! {
+                <Profile />
+            }
+        }
 
-//         RouteBody::Role => {
-//             html! {
-//                 <RoleList />
-//             }
-//         }
-//     }
-// }
+        RouteBody::Sessions => {
+            html! {
+                <Sessions />
+            }
+        }
+    }
+}
 
 fn switch(route: Route) -> Html {
     match route {
@@ -90,6 +125,11 @@ fn switch(route: Route) -> Html {
                 <Register />
             }
         }
+        Route::SsoCallback => {
+            html! {
+                <SsoCallback />
+            }
+        }
         // Route::ForgetPwd => {
         //     html! {
         //         <ForgetPwd />
@@ -100,24 +140,24 @@ fn switch(route: Route) -> Html {
         //         <Layout content={html!{<Welcome greeting={"Welcome to Pied Piper!"} />}}/>
         //     }
         // }
-        // Route::Body => {
-        //     let menus = vec![MenuLabel {
-        //         label: Some(String::from("User Management")),
-        //         nodes: vec![
-        //             MenuNode {
-        //                 name: String::from("User"),
-        //                 children: Default::default(),
-        //             },
-        //             MenuNode {
-        //                 name: String::from("Role"),
-        //                 children: Default::default(),
-        //             },
-        //         ],
-        //     }];
-        //     html! {
-        //         <Layout menus = {menus} content={html!{<Switch<RouteBody> render={switch_body} />}}/>
-        //     }
-        // }
+        Route::Body => {
+            let menus = vec![MenuLabel {
+                label: Some(String::from("User Management")),
+                nodes: vec![
+                    MenuNode {
+                        name: String::from("User"),
+                        ..Default::default()
+                    },
+                    MenuNode {
+                        name: String::from("Role"),
+                        ..Default::default()
+                    },
+                ],
+            }];
+            html! {
+                <Layout menus = {menus} content={html!{<Switch<RouteBody> render={switch_body} />}}/>
+            }
+        }
         Route::Unauthorized => {
             html! {
                 <RequestError status={401} />
@@ -141,13 +181,18 @@ fn switch(route: Route) -> Html {
 #[function_component(Main)]
 fn app() -> Html {
     html! {
-        <BrowserRouter>
-            <Switch<Route> render={switch} />
-        </BrowserRouter>
+        <I18nProvider>
+            <ThemeProvider>
+                <BrowserRouter>
+                    <Switch<Route> render={switch} />
+                </BrowserRouter>
+            </ThemeProvider>
+        </I18nProvider>
     }
 }
 
 fn main() {
     wasm_logger::init(wasm_logger::Config::default());
+    util::credential::purge_legacy();
     yew::Renderer::<Main>::new().render();
 }
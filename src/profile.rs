@@ -0,0 +1,639 @@
+use crate::component::timestamp::Timestamp;
+use crate::component::toast::use_toast;
+use crate::util::common::{self, CurrentUser, CurrentUserHandle};
+use crate::util::error::ToError;
+use crate::util::form::{self, use_form, Field, InputField};
+use crate::util::i18n::{self, use_translation};
+use crate::util::qr;
+use crate::util::request::{self, Host};
+use crate::util::sanitize;
+use crate::util::sso::{self, Identity, Intent, SsoConfig};
+use crate::util::verify_code::{
+    use_code_sender, CodeButton, CodeChallenge, CodePurpose, CodeStatus,
+};
+use evolve_axum_cli::apis::user_api;
+use serde::{Deserialize, Serialize};
+use wasm_bindgen_futures::spawn_local;
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+
+const MAX_AVATAR_SIZE: f64 = 2.0 * 1024.0 * 1024.0;
+
+// the generated client has no update of the signed-in user, the server takes
+// the user from the token
+#[derive(Serialize)]
+struct UpdateDetailsReq {
+    name: String,
+    mobile: String,
+}
+
+#[derive(Serialize)]
+struct ChangeEmailReq {
+    email: String,
+    code: String,
+}
+
+// the cached user is changed and the header gets it at once
+fn update_current_user(handle: &Option<CurrentUserHandle>, f: impl FnOnce(&mut CurrentUser)) {
+    let mut user = match common::get_current_user() {
+        Ok(v) => v,
+        Err(err) => return log::warn!("get current user error: {}", err),
+    };
+    f(&mut user);
+    if let Err(err) = common::set_current_user(&user) {
+        log::error!("set current user error: {}", err);
+    }
+    if let Some(handle) = handle {
+        handle.set(Some(user));
+    }
+}
+
+async fn validate_not_exist_email(email: String) -> common::BasicResult<()> {
+    user_api::validate_not_exist_email(&common::get_cli_config_without_token().unwrap(), &email)
+        .await
+        .map(|_| ())
+        .map_err(|err| match err {
+            evolve_axum_cli::apis::Error::ResponseError(res_err) => match res_err.entity {
+                Some(user_api::ValidateNotExistEmailError::Status400(e))
+                | Some(user_api::ValidateNotExistEmailError::Status500(e)) => {
+                    e.message.to_validation_error()
+                }
+                _ => res_err.content.to_validation_error(),
+            },
+            _ => err.to_string().to_validation_error(),
+        })
+}
+
+#[derive(PartialEq, Properties)]
+struct DetailsProps {
+    user: CurrentUser,
+}
+
+#[function_component(Details)]
+fn details(props: &DetailsProps) -> Html {
+    let i18n = use_translation();
+    let toaster = use_toast();
+    let current_user = use_context::<CurrentUserHandle>();
+    let form = {
+        let user = props.user.clone();
+        use_form(move || form::user_details(user.name.as_deref(), user.mobile.as_deref()))
+    };
+    let saving = use_state(|| false);
+
+    let on_save = {
+        let form = form.clone();
+        let saving = saving.clone();
+        Callback::from(move |_| {
+            if *saving || !form.touch_all() {
+                return;
+            }
+            let name = form.value("name");
+            let mobile = form.value("mobile");
+            let req = UpdateDetailsReq {
+                name: name.clone(),
+                mobile: mobile.clone(),
+            };
+            saving.set(true);
+            let toaster = toaster.clone();
+            let current_user = current_user.clone();
+            let saving = saving.clone();
+            spawn_local(async move {
+                match request::put::<(), _>(Host::AxumBase, "/user/details", &req).await {
+                    Ok(_) => {
+                        update_current_user(&current_user, |user| {
+                            user.name = Some(name);
+                            user.mobile = Some(mobile);
+                        });
+                        toaster.ok(&i18n::t("profile.saved"));
+                    }
+                    Err(err) => toaster.error(&err.to_string()),
+                }
+                saving.set(false);
+            });
+        })
+    };
+
+    html! {
+        <div class="box">
+            <p class="title is-5">{i18n.t("profile.details")}</p>
+            <InputField form={form.clone()} name="name" label={i18n.t("user.name")} placeholder="Scarlett" idle=""
+                onenter={on_save.clone()}/>
+            <InputField form={form.clone()} name="mobile" label={i18n.t("user.mobile")} placeholder="13800001111" idle=""
+                onenter={on_save.clone()}/>
+            <button class={classes!("button", "is-primary", saving.then_some("is-loading"))}
+                disabled={!form.can_submit() || !form.is_form_dirty() || *saving} onclick={on_save.reform(|_| ())}>
+                {i18n.t("user.save")}
+            </button>
+        </div>
+    }
+}
+
+#[function_component(ChangeEmail)]
+fn change_email() -> Html {
+    let i18n = use_translation();
+    let toaster = use_toast();
+    let current_user = use_context::<CurrentUserHandle>();
+    let form = use_form(|| {
+        vec![
+            Field::new("email")
+                .validate(form::email())
+                .validate_async(form::async_validator(validate_not_exist_email)),
+            Field::new("code").validate(form::code()),
+        ]
+    });
+    let code = use_code_sender(CodePurpose::ChangeEmail, &form.value("email"));
+    let saving = use_state(|| false);
+
+    let on_save = {
+        let form = form.clone();
+        let code = code.clone();
+        let saving = saving.clone();
+        Callback::from(move |_| {
+            if *saving || !form.touch_all() {
+                return;
+            }
+            let email = form.value("email");
+            let req = ChangeEmailReq {
+                email: email.clone(),
+                code: form.value("code"),
+            };
+            saving.set(true);
+            let form = form.clone();
+            let toaster = toaster.clone();
+            let current_user = current_user.clone();
+            let code = code.clone();
+            let saving = saving.clone();
+            spawn_local(async move {
+                match request::put::<(), _>(Host::AxumBase, "/user/email", &req).await {
+                    Ok(_) => {
+                        update_current_user(&current_user, |user| user.email = email);
+                        form.reset();
+                        code.reset();
+                        toaster.ok(&i18n::t("profile.email_changed"));
+                    }
+                    Err(err) => form.set_error("code", &err.to_string()),
+                }
+                saving.set(false);
+            });
+        })
+    };
+
+    let code_button = html! {
+        <CodeButton sender={code.clone()} disabled={!form.is_valid("email")} class="is-info"/>
+    };
+
+    html! {
+        <div class="box">
+            <p class="title is-5">{i18n.t("profile.change_email")}</p>
+            <InputField form={form.clone()} name="email" label={i18n.t("profile.new_email")} input_type="email"
+                placeholder="hello@example.com" autocomplete="email" icon="fa-solid fa-envelope" idle="" addon={code_button}/>
+            <CodeChallenge sender={code.clone()}/>
+            <InputField form={form.clone()} name="code" label={i18n.t("register.code")} placeholder="123456"
+                icon="fa-solid fa-barcode" idle="" disabled={!code.sent} onenter={on_save.clone()}/>
+            <CodeStatus sender={code.clone()}/>
+            <button class={classes!("button", "is-primary", saving.then_some("is-loading"))}
+                disabled={!form.can_submit() || *saving} onclick={on_save.reform(|_| ())}>
+                {i18n.t("profile.change_email")}
+            </button>
+        </div>
+    }
+}
+
+#[derive(Serialize)]
+struct TotpCodeReq {
+    code: String,
+}
+
+#[derive(Deserialize, Clone, PartialEq)]
+struct TotpSetup {
+    secret: String,
+    // otpauth://totp/... scanned by the authenticator app
+    url: String,
+}
+
+#[derive(Clone, PartialEq)]
+enum TotpState {
+    Loading,
+    // the status could not be loaded, a retry is offered
+    Failed(String),
+    Disabled,
+    // the secret is only kept by the server once a code confirmed it
+    Enrolling(TotpSetup),
+    // recovery codes are shown once, right after enabling
+    Enabled(Option<Vec<String>>),
+}
+
+fn recovery_codes_url(codes: &[String]) -> String {
+    let text = js_sys::encode_uri_component(&codes.join("\n"));
+    format!("data:text/plain;charset=utf-8,{}", String::from(text))
+}
+
+#[function_component(TwoFactor)]
+fn two_factor() -> Html {
+    let i18n = use_translation();
+    let toaster = use_toast();
+    let state = use_state(|| TotpState::Loading);
+    let form = use_form(|| vec![Field::new("code").validate(form::code())]);
+    let submitting = use_state(|| false);
+
+    let reload = {
+        let state = state.clone();
+        Callback::from(move |_: ()| {
+            state.set(TotpState::Loading);
+            let state = state.clone();
+            spawn_local(async move {
+                match request::get::<bool, Vec<(&str, &str)>, _>(Host::AxumBase, "/user/totp", None)
+                    .await
+                {
+                    Ok(res) => state.set(match res.data.unwrap_or_default() {
+                        true => TotpState::Enabled(None),
+                        false => TotpState::Disabled,
+                    }),
+                    Err(err) => state.set(TotpState::Failed(err.to_string())),
+                }
+            });
+        })
+    };
+    {
+        let reload = reload.clone();
+        use_effect_with((), move |_| reload.emit(()));
+    }
+
+    let on_setup = {
+        let state = state.clone();
+        let toaster = toaster.clone();
+        let submitting = submitting.clone();
+        Callback::from(move |_: MouseEvent| {
+            submitting.set(true);
+            let state = state.clone();
+            let toaster = toaster.clone();
+            let submitting = submitting.clone();
+            spawn_local(async move {
+                match request::post::<TotpSetup, _>(Host::AxumBase, "/user/totp/setup", &()).await {
+                    Ok(res) => {
+                        if let Some(setup) = res.data {
+                            state.set(TotpState::Enrolling(setup));
+                        }
+                    }
+                    Err(err) => toaster.error(&err.to_string()),
+                }
+                submitting.set(false);
+            });
+        })
+    };
+
+    // enables while enrolling, disables otherwise, both need a current code
+    let on_confirm = {
+        let state = state.clone();
+        let form = form.clone();
+        let submitting = submitting.clone();
+        Callback::from(move |_| {
+            if *submitting || !form.touch_all() {
+                return;
+            }
+            let enrolling = matches!(*state, TotpState::Enrolling(_));
+            let req = TotpCodeReq {
+                code: form.value("code"),
+            };
+            submitting.set(true);
+            let state = state.clone();
+            let form = form.clone();
+            let toaster = toaster.clone();
+            let submitting = submitting.clone();
+            spawn_local(async move {
+                let res = match enrolling {
+                    true => {
+                        request::post::<Vec<String>, _>(Host::AxumBase, "/user/totp/enable", &req)
+                            .await
+                            .map(|x| TotpState::Enabled(Some(x.data.unwrap_or_default())))
+                    }
+                    false => request::post::<(), _>(Host::AxumBase, "/user/totp/disable", &req)
+                        .await
+                        .map(|_| TotpState::Disabled),
+                };
+                match res {
+                    Ok(v) => {
+                        form.reset();
+                        toaster.ok(&i18n::t(match enrolling {
+                            true => "profile.totp_enabled",
+                            false => "profile.totp_disabled",
+                        }));
+                        state.set(v);
+                    }
+                    Err(err) => form.set_error("code", &err.to_string()),
+                }
+                submitting.set(false);
+            });
+        })
+    };
+
+    let code_field = html! {
+        <InputField form={form.clone()} name="code" label={i18n.t("profile.totp_code")} placeholder="123456"
+            autocomplete="one-time-code" icon="fa-solid fa-shield-halved" idle="" onenter={on_confirm.clone()}/>
+    };
+    let content = match &*state {
+        TotpState::Loading => html! { <progress class="progress is-small is-info" max="100"/> },
+        TotpState::Failed(msg) => html! {
+            <>
+            <p class="help is-danger mb-3">{msg.clone()}</p>
+            <button class="button" onclick={reload.reform(|_| ())}>
+                {i18n.t("profile.totp_retry")}
+            </button>
+            </>
+        },
+        TotpState::Disabled => html! {
+            <>
+            <p class="mb-3">{i18n.t("profile.totp_off")}</p>
+            <button class={classes!("button", "is-primary", submitting.then_some("is-loading"))}
+                disabled={*submitting} onclick={on_setup}>
+                {i18n.t("profile.totp_setup")}
+            </button>
+            </>
+        },
+        TotpState::Enrolling(setup) => html! {
+            <>
+            <p class="mb-3">{i18n.t("profile.totp_scan")}</p>
+            <div class="has-text-centered mb-3">{qr::render(&setup.url, 200)}</div>
+            <p class="mb-3">{i18n.t("profile.totp_secret")}<code>{setup.secret.clone()}</code></p>
+            {code_field}
+            <button class={classes!("button", "is-primary", submitting.then_some("is-loading"))}
+                disabled={!form.can_submit() || *submitting} onclick={on_confirm.reform(|_| ())}>
+                {i18n.t("profile.totp_enable")}
+            </button>
+            </>
+        },
+        TotpState::Enabled(codes) => html! {
+            <>
+            <p class="mb-3">{i18n.t("profile.totp_on")}</p>
+            if let Some(codes) = codes {
+                <div class="notification is-warning is-light">
+                    <p class="mb-2">{i18n.t("profile.recovery_codes_hint")}</p>
+                    <div class="content"><pre>{codes.join("\n")}</pre></div>
+                    <a class="button is-small" href={recovery_codes_url(codes)} download="recovery-codes.txt">
+                        <span class="icon"><i class="fa-solid fa-download"></i></span>
+                        <span>{i18n.t("profile.recovery_codes_download")}</span>
+                    </a>
+                </div>
+            }
+            {code_field}
+            <button class={classes!("button", "is-danger", submitting.then_some("is-loading"))}
+                disabled={!form.can_submit() || *submitting} onclick={on_confirm.reform(|_| ())}>
+                {i18n.t("profile.totp_disable")}
+            </button>
+            </>
+        },
+    };
+
+    html! {
+        <div class="box">
+            <p class="title is-5">{i18n.t("profile.totp")}</p>
+            {content}
+        </div>
+    }
+}
+
+// identities of the company provider, a linked one signs in without a password
+#[function_component(SsoIdentities)]
+fn sso_identities() -> Html {
+    let i18n = use_translation();
+    let toaster = use_toast();
+    let config = use_state(|| None::<SsoConfig>);
+    // None while loading
+    let identities = use_state(|| None::<Vec<Identity>>);
+    let unlinking = use_state(|| None::<String>);
+
+    {
+        let config = config.clone();
+        let identities = identities.clone();
+        let toaster = toaster.clone();
+        use_effect_with((), move |_| {
+            spawn_local(async move {
+                config.set(sso::config().await);
+                match sso::identities().await {
+                    Ok(v) => identities.set(Some(v)),
+                    Err(err) => toaster.error(&err.to_string()),
+                }
+            });
+        });
+    }
+
+    let on_link = {
+        let config = config.clone();
+        let toaster = toaster.clone();
+        Callback::from(move |_: MouseEvent| {
+            if let Some(config) = &*config {
+                if let Err(err) = sso::start(config, Intent::Link) {
+                    toaster.error(&err.to_string());
+                }
+            }
+        })
+    };
+
+    let on_unlink = {
+        let identities = identities.clone();
+        let unlinking = unlinking.clone();
+        Callback::from(move |id: String| {
+            unlinking.set(Some(id.clone()));
+            let identities = identities.clone();
+            let unlinking = unlinking.clone();
+            let toaster = toaster.clone();
+            spawn_local(async move {
+                match sso::unlink(&id).await {
+                    Ok(_) => {
+                        toaster.ok(&i18n::t("profile.sso_unlinked"));
+                        let rest = (*identities)
+                            .clone()
+                            .map(|x| x.into_iter().filter(|x| x.id != id).collect());
+                        identities.set(rest);
+                    }
+                    Err(err) => toaster.error(&err.to_string()),
+                }
+                unlinking.set(None);
+            });
+        })
+    };
+
+    // nothing to show when sso is not configured and nothing was linked before
+    let config = match (&*config, &*identities) {
+        (None, Some(items)) if items.is_empty() => return html! {},
+        (config, _) => config.clone(),
+    };
+    let content = match &*identities {
+        None => html! { <progress class="progress is-small is-info" max="100"/> },
+        Some(items) if items.is_empty() => {
+            html! { <p class="mb-3">{i18n.t("profile.sso_none")}</p> }
+        }
+        Some(items) => html! {
+            <table class="table is-fullwidth">
+                <tbody>
+                {
+                    items.iter().map(|item| {
+                        let busy = unlinking.as_deref() == Some(item.id.as_str());
+                        let onclick = {
+                            let id = item.id.clone();
+                            on_unlink.reform(move |_: MouseEvent| id.clone())
+                        };
+                        html! {
+                            <tr key={item.id.clone()}>
+                                <td>{item.provider.clone()}</td>
+                                <td>{item.email.clone().unwrap_or_default()}</td>
+                                <td><Timestamp value={item.created_at} relative=false/></td>
+                                <td class="has-text-right">
+                                    <button class={classes!("button", "is-small", "is-danger", "is-outlined", busy.then_some("is-loading"))}
+                                        disabled={unlinking.is_some()} {onclick}>
+                                        {i18n.t("profile.sso_unlink")}
+                                    </button>
+                                </td>
+                            </tr>
+                        }
+                    }).collect::<Html>()
+                }
+                </tbody>
+            </table>
+        },
+    };
+
+    html! {
+        <div class="box">
+            <p class="title is-5">{i18n.t("profile.sso")}</p>
+            {content}
+            if let Some(config) = config {
+                <button class="button is-primary" onclick={on_link}>
+                    <span class="icon"><i class="fa-solid fa-building-shield"></i></span>
+                    <span>{i18n.t_with("profile.sso_link", &[("name", &config.name)])}</span>
+                </button>
+            }
+        </div>
+    }
+}
+
+#[derive(PartialEq, Properties)]
+struct AvatarProps {
+    avatar: Option<String>,
+}
+
+#[function_component(Avatar)]
+fn avatar(props: &AvatarProps) -> Html {
+    let i18n = use_translation();
+    let toaster = use_toast();
+    let current_user = use_context::<CurrentUserHandle>();
+    let file_ref = use_node_ref();
+    let progress = use_state(|| None::<u32>);
+
+    let on_choose = {
+        let file_ref = file_ref.clone();
+        Callback::from(move |_: MouseEvent| {
+            if let Some(input) = file_ref.cast::<HtmlInputElement>() {
+                input.click();
+            }
+        })
+    };
+    let on_change = {
+        let progress = progress.clone();
+        Callback::from(move |e: Event| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            let file = match input.files().and_then(|x| x.get(0)) {
+                Some(v) => v,
+                None => return,
+            };
+            input.set_value("");
+            if !file.type_().starts_with("image/") || file.size() > MAX_AVATAR_SIZE {
+                return toaster.error(&i18n::t("profile.avatar_invalid"));
+            }
+            progress.set(Some(0));
+            let toaster = toaster.clone();
+            let current_user = current_user.clone();
+            let progress = progress.clone();
+            spawn_local(async move {
+                let onprogress = {
+                    let progress = progress.clone();
+                    Callback::from(move |x: u32| progress.set(Some(x)))
+                };
+                match request::upload::<String>(Host::AxumBase, "/user/avatar", &file, onprogress)
+                    .await
+                {
+                    Ok(res) => {
+                        if let Some(url) = res.data {
+                            update_current_user(&current_user, |user| user.avatar = Some(url));
+                        }
+                    }
+                    Err(err) => toaster.error(&err.to_string()),
+                }
+                progress.set(None);
+            });
+        })
+    };
+
+    html! {
+        <div class="has-text-centered">
+            <figure class="image is-96x96 is-inline-block profile-avatar">
+            {
+                match props.avatar.as_deref().filter(|x| sanitize::is_safe_url(x)) {
+                    Some(avatar) => html!{ <img class="is-rounded" src={avatar.to_string()} alt=""/> },
+                    None => html!{ <span class="icon is-large has-text-grey"><i class="fa-solid fa-circle-user fa-5x"></i></span> },
+                }
+            }
+            </figure>
+            if let Some(progress) = *progress {
+                <progress class="progress is-small is-info" value={progress.to_string()} max="100"/>
+            }
+            <input ref={file_ref} class="is-hidden" type="file" accept="image/*" onchange={on_change}/>
+            <button class="button is-small" disabled={progress.is_some()} onclick={on_choose}>
+                <span class="icon"><i class="fa-solid fa-upload"></i></span>
+                <span>{i18n.t("profile.upload_avatar")}</span>
+            </button>
+        </div>
+    }
+}
+
+#[function_component(Profile)]
+pub fn profile() -> Html {
+    let i18n = use_translation();
+    let current_user = use_context::<CurrentUserHandle>();
+    let user = match current_user
+        .and_then(|x| (*x).clone())
+        .or_else(|| common::get_current_user().ok())
+    {
+        Some(v) => v,
+        None => {
+            common::redirect("/login");
+            return html! {};
+        }
+    };
+
+    html! {
+        <div class="columns is-centered">
+            <div class="column is-half">
+                <div class="box">
+                    <Avatar avatar={user.avatar.clone()}/>
+                    <table class="table is-fullwidth">
+                        <tbody>
+                            <tr><th>{i18n.t("user.email")}</th><td>{user.email.clone()}</td></tr>
+                            <tr><th>{i18n.t("user.type")}</th><td>{user.r#type.clone()}</td></tr>
+                            <tr>
+                                <th>{i18n.t("user.laston")}</th>
+                                <td>
+                                {
+                                    match user.laston {
+                                        Some(laston) => html!{ <Timestamp value={laston}/> },
+                                        None => html!{ {i18n.t("time.never")} },
+                                    }
+                                }
+                                </td>
+                            </tr>
+                            <tr>
+                                <th>{i18n.t("user.created_at")}</th>
+                                <td><Timestamp value={user.created_at} relative=false/></td>
+                            </tr>
+                        </tbody>
+                    </table>
+                </div>
+                // saved details become the initial values of a new form
+                <Details key={format!("{:?}{:?}", user.name, user.mobile)} user={user.clone()}/>
+                <ChangeEmail/>
+                <TwoFactor/>
+                <SsoIdentities/>
+            </div>
+        </div>
+    }
+}
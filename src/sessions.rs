@@ -0,0 +1,165 @@
+use crate::component::timestamp::Timestamp;
+use crate::component::toast::use_toast;
+use crate::confirm_form::ConfirmForm;
+use crate::util::common;
+use crate::util::i18n::{self, use_translation};
+use crate::util::session::{self, Session};
+use wasm_bindgen_futures::spawn_local;
+use yew::prelude::*;
+
+#[function_component(Sessions)]
+pub fn sessions_page() -> Html {
+    let i18n = use_translation();
+    let toaster = use_toast();
+    // None while loading
+    let sessions = use_state(|| None::<Vec<Session>>);
+    let revoking = use_state(|| None::<String>);
+    let confirming = use_state(|| false);
+
+    let reload = {
+        let sessions = sessions.clone();
+        let toaster = toaster.clone();
+        Callback::from(move |_: ()| {
+            let sessions = sessions.clone();
+            let toaster = toaster.clone();
+            spawn_local(async move {
+                match session::list().await {
+                    Ok(v) => sessions.set(Some(v)),
+                    Err(err) => toaster.error(&err.to_string()),
+                }
+            });
+        })
+    };
+    {
+        let reload = reload.clone();
+        use_effect_with((), move |_| reload.emit(()));
+    }
+
+    // signing out the current session is a logout
+    let on_revoke = {
+        let toaster = toaster.clone();
+        let revoking = revoking.clone();
+        Callback::from(move |item: Session| {
+            if item.current {
+                spawn_local(async move {
+                    session::logout().await;
+                    common::redirect("/login");
+                });
+                return;
+            }
+            revoking.set(Some(item.id.clone()));
+            let toaster = toaster.clone();
+            let revoking = revoking.clone();
+            let reload = reload.clone();
+            spawn_local(async move {
+                match session::revoke(&item.id).await {
+                    Ok(_) => {
+                        toaster.ok(&i18n::t("sessions.revoked"));
+                        reload.emit(());
+                    }
+                    Err(err) => toaster.error(&err.to_string()),
+                }
+                revoking.set(None);
+            });
+        })
+    };
+
+    let confirm = {
+        let confirming = confirming.clone();
+        Callback::from(move |_: MouseEvent| confirming.set(true))
+    };
+    let cancel_confirm = {
+        let confirming = confirming.clone();
+        Callback::from(move |_| confirming.set(false))
+    };
+
+    let on_revoke_all = {
+        let confirming = confirming.clone();
+        Callback::from(move |_| {
+            confirming.set(false);
+            let toaster = toaster.clone();
+            spawn_local(async move {
+                match session::revoke_all().await {
+                    Ok(_) => {
+                        session::clear_local();
+                        common::redirect("/login");
+                    }
+                    Err(err) => toaster.error(&err.to_string()),
+                }
+            });
+        })
+    };
+
+    let content = match &*sessions {
+        None => html! { <progress class="progress is-small is-info" max="100"/> },
+        Some(items) => html! {
+            <table class="table is-fullwidth is-hoverable">
+                <thead>
+                    <tr>
+                        <th>{i18n.t("sessions.device")}</th>
+                        <th>{i18n.t("sessions.ip")}</th>
+                        <th>{i18n.t("sessions.last_seen")}</th>
+                        <th>{i18n.t("sessions.created_at")}</th>
+                        <th></th>
+                    </tr>
+                </thead>
+                <tbody>
+                {
+                    items.iter().map(|item| {
+                        let agent = item.user_agent.clone().unwrap_or_default();
+                        let busy = revoking.as_deref() == Some(item.id.as_str());
+                        let onclick = {
+                            let item = item.clone();
+                            on_revoke.reform(move |_: MouseEvent| item.clone())
+                        };
+                        html! {
+                            <tr key={item.id.clone()}>
+                                <td title={agent.clone()}>
+                                    {
+                                        match agent.is_empty() {
+                                            true => i18n.t("sessions.unknown_device"),
+                                            false => session::describe_agent(&agent),
+                                        }
+                                    }
+                                    if item.current {
+                                        <span class="tag is-success is-light ml-2">{i18n.t("sessions.current")}</span>
+                                    }
+                                </td>
+                                <td>{item.ip.clone().unwrap_or_default()}</td>
+                                <td><Timestamp value={item.last_seen}/></td>
+                                <td><Timestamp value={item.created_at} relative=false/></td>
+                                <td class="has-text-right">
+                                    <button class={classes!("button", "is-small", "is-danger", "is-outlined", busy.then_some("is-loading"))}
+                                        disabled={revoking.is_some()} {onclick}>
+                                        {i18n.t("sessions.revoke")}
+                                    </button>
+                                </td>
+                            </tr>
+                        }
+                    }).collect::<Html>()
+                }
+                </tbody>
+            </table>
+        },
+    };
+
+    html! {
+        <div class="columns is-centered">
+            <div class="column is-two-thirds">
+                <div class="box">
+                    <p class="title is-4">{i18n.t("sessions.title")}</p>
+                    <p class="subtitle is-6">{i18n.t("sessions.hint")}</p>
+                    {content}
+                    <button class="button is-danger" disabled={sessions.is_none()} onclick={confirm}>
+                        <span class="icon"><i class="fa-solid fa-right-from-bracket"></i></span>
+                        <span>{i18n.t("sessions.revoke_all")}</span>
+                    </button>
+                </div>
+                if *confirming {
+                    <ConfirmForm onclose={cancel_confirm} onconfirm={on_revoke_all}
+                        content={i18n.t("sessions.revoke_all_confirm")}/>
+                }
+            </div>
+        </div>
+    }
+}
@@ -1,11 +1,11 @@
-use crate::component::message_item::MessageItemValue;
-use crate::component::message_list::{self, MessageList};
+use crate::component::toast::use_toast;
 use crate::util::common;
+use crate::util::form::{self, use_form, InputField};
+use crate::util::i18n::use_translation;
 use user_cli::apis::user_controller_api::{self, UpdateError};
 use user_cli::apis::Error;
 use user_cli::models::{User, UserUpdateReq};
 use wasm_bindgen_futures::spawn_local;
-use web_sys::{Event, HtmlInputElement};
 use yew::prelude::*;
 use yew::Properties;
 
@@ -20,10 +20,13 @@ pub struct UserFormProps {
 
 #[function_component(UserForm)]
 pub fn user_form(props: &UserFormProps) -> Html {
-    let messages: std::rc::Rc<std::cell::RefCell<Option<MessageItemValue>>> =
-        use_mut_ref(|| Default::default());
-    let value = use_mut_ref(|| props.value.clone());
-    let force_update = use_force_update();
+    let toaster = use_toast();
+    let i18n = use_translation();
+    let form = {
+        let value = props.value.clone();
+        use_form(move || form::user_details(value.name.as_deref(), value.mobile.as_deref()))
+    };
+    let saving = use_state(|| false);
 
     let close = {
         let onclose = props.onclose.clone();
@@ -32,94 +35,77 @@ pub fn user_form(props: &UserFormProps) -> Html {
         })
     };
     let update = {
-        let value = value.clone();
-        let messages = messages.clone();
+        let id = props.value.id;
+        let form = form.clone();
+        let saving = saving.clone();
+        let toaster = toaster.clone();
         let onupdate = props.onupdate.clone();
-        let force_update = force_update.clone();
-        Callback::from(move |_e: MouseEvent| {
-            let messages = messages.clone();
+        Callback::from(move |_| {
+            if *saving || !form.touch_all() {
+                return;
+            }
+            let toaster = toaster.clone();
             let onupdate = onupdate.clone();
-            let force_update = force_update.clone();
-            let value = value.clone();
-            let value = value.borrow();
+            let saving = saving.clone();
             let req = UserUpdateReq {
-                id: value.id,
-                mobile: value.mobile.clone(),
-                name: value.name.clone(),
+                id,
+                mobile: Some(form.value("mobile")),
+                name: Some(form.value("name")),
             };
+            saving.set(true);
             spawn_local(async move {
                 match user_controller_api::update(&common::get_cli_config().unwrap(), req).await {
                     Ok(_) => {
                         onupdate.emit(());
                     }
-                    Err(err) => {
-                        match err {
-                            Error::ResponseError(res_err) => match res_err.entity {
-                                Some(UpdateError::Status400(e))
-                                | Some(UpdateError::Status401(e))
-                                | Some(UpdateError::Status500(e)) => {
-                                    *messages.borrow_mut() =
-                                        Some(message_list::error(&format!("{}", e.msg)));
-                                }
-                                _ => {
-                                    *messages.borrow_mut() =
-                                        Some(message_list::error(&format!("{}", res_err.content)));
-                                }
-                            },
+                    Err(err) => match err {
+                        Error::ResponseError(res_err) => match res_err.entity {
+                            Some(UpdateError::Status400(e))
+                            | Some(UpdateError::Status401(e))
+                            | Some(UpdateError::Status500(e)) => {
+                                toaster.error(&format!("{}", e.msg));
+                            }
                             _ => {
-                                *messages.borrow_mut() =
-                                    Some(message_list::error(&format!("{}", err)));
+                                toaster.error(&format!("{}", res_err.content));
                             }
+                        },
+                        _ => {
+                            toaster.error(&format!("{}", err));
                         }
-                        force_update.force_update();
-                    }
+                    },
                 };
+                saving.set(false);
             });
         })
     };
-    let name_change = {
-        let value = value.clone();
-        Callback::from(move |e: Event| {
-            let el: HtmlInputElement = e.target_unchecked_into();
-            value.borrow_mut().name = Some(el.value());
-        })
-    };
-    let mobile_change = {
-        let value = value.clone();
-        Callback::from(move |e: Event| {
-            let el: HtmlInputElement = e.target_unchecked_into();
-            value.borrow_mut().mobile = Some(el.value());
-        })
-    };
-    let val = value.borrow();
+    let val = &props.value;
     html! {
         <div class="modal is-active">
             <div class="modal-background"></div>
             <div class="modal-card">
-                <MessageList value={messages.borrow().clone()}/>
                 <header class="modal-card-head">
-                <p class="modal-card-title">{"User Edit"}</p>
+                <p class="modal-card-title">{i18n.t("user.edit_title")}</p>
                 <button class="delete" aria-label="close" onclick={close.clone()}></button>
                 </header>
                 <section class="modal-card-body">
 
                 <fieldset disabled={true}>
                 <div class="field">
-                    <label class="label">{"Type"}</label>
+                    <label class="label">{i18n.t("user.type")}</label>
                     <div class="control">
                     <input class="input" value={val.r#type.to_string().clone()} type="text" />
                     </div>
                 </div>
 
                 <div class="field">
-                    <label class="label">{"Email"}</label>
+                    <label class="label">{i18n.t("user.email")}</label>
                     <div class="control">
                     <input class="input" value={val.email.clone()} type="email" />
                     </div>
                 </div>
 
                 <div class="field">
-                    <label class="label">{"Status"}</label>
+                    <label class="label">{i18n.t("user.status")}</label>
                     <div class="control">
                     <input class="input" value={val.status.to_string().clone()} />
                     </div>
@@ -128,24 +114,16 @@ pub fn user_form(props: &UserFormProps) -> Html {
 
 
 
-                <div class="field">
-                    <label class="label">{"Name"}</label>
-                    <div class="control">
-                    <input class="input" value={val.name.clone()} type="text" placeholder="Scarlett" onchange={name_change}/>
-                    </div>
-                </div>
-
-                <div class="field">
-                    <label class="label">{"Mobile"}</label>
-                    <div class="control">
-                    <input class="input" value={val.mobile.clone()} type="text" placeholder="13800001111" onchange={mobile_change}/>
-                    </div>
-                </div>
+                <InputField form={form.clone()} name="name" label={i18n.t("user.name")} placeholder="Scarlett" idle=""
+                    onenter={update.clone()}/>
+                <InputField form={form.clone()} name="mobile" label={i18n.t("user.mobile")} placeholder="13800001111" idle=""
+                    onenter={update.clone()}/>
 
                 </section>
                 <footer class="modal-card-foot">
-                <button class="button is-success"  onclick={update}>{"Save changes"}</button>
-                <button class="button" onclick={close} >{"Cancel"}</button>
+                <button class={classes!("button", "is-success", saving.then_some("is-loading"))}
+                    disabled={!form.can_submit() || !form.is_form_dirty() || *saving} onclick={update.reform(|_| ())}>{i18n.t("user.save")}</button>
+                <button class="button" onclick={close} >{i18n.t("common.cancel")}</button>
                 </footer>
             </div>
         </div>
@@ -1,9 +1,13 @@
-use crate::component::message_list::{self, MessageList};
+use crate::component::message_list::MessageList;
 use crate::component::pager::{self, Page, Pager};
+use crate::component::timestamp::Timestamp;
+use crate::component::toast::use_toast;
 use crate::confirm_form::ConfirmForm;
 use crate::user_form::UserForm;
 
 use crate::util::common;
+use crate::util::i18n::{self, use_translation};
+use crate::util::highlight::highlight;
 use serde::Serialize;
 use std::cell::RefCell;
 use std::rc::Rc;
@@ -22,8 +26,11 @@ pub fn user_list() -> Html {
     let refresh_list = use_state(|| false);
     let force_update = use_force_update();
     let selected_row: Rc<RefCell<Option<User>>> = use_mut_ref(|| None);
-    let message = use_mut_ref(|| None);
+    let toaster = use_toast();
+    let i18n = use_translation();
     let key_word = use_mut_ref(|| String::default());
+    // keyword of the loaded page, matches in it are highlighted
+    let searched = use_mut_ref(|| String::default());
     let user_form_closed = use_mut_ref(|| true);
     let confirm_form_closed = use_mut_ref(|| true);
     let loading = use_mut_ref(|| false);
@@ -32,8 +39,9 @@ pub fn user_list() -> Html {
     let size = use_mut_ref(|| pager::DEFAULT_PAGE_SIZE);
     let data = use_mut_ref(|| Default::default());
     {
-        let message = message.clone();
+        let toaster = toaster.clone();
         let key_word = key_word.clone();
+        let searched = searched.clone();
         let index = index.clone();
         let size = size.clone();
         let total = total.clone();
@@ -43,10 +51,11 @@ pub fn user_list() -> Html {
         let refresh_list = refresh_list.clone();
         let selected_row = selected_row.clone();
         use_effect_with(refresh_list, move |_| {
+            let keyword = key_word.borrow().clone();
             spawn_local(async move {
                 match user_controller_api::search(
                     &common::get_cli_config().unwrap(),
-                    key_word.borrow().as_str(),
+                    keyword.as_str(),
                     *index.borrow(),
                     *size.borrow() as i64,
                 )
@@ -54,12 +63,13 @@ pub fn user_list() -> Html {
                 {
                     Ok(res) => {
                         *data.borrow_mut() = res.data;
+                        *searched.borrow_mut() = keyword;
                         *total.borrow_mut() = res.total;
                         *loading.borrow_mut() = false;
                         force_update.force_update();
                     }
                     Err(err) => {
-                        *message.borrow_mut() = Some(message_list::error(&format!("{}", err)));
+                        toaster.error(&format!("{}", err));
                         *loading.borrow_mut() = false;
                     }
                 };
@@ -103,10 +113,10 @@ pub fn user_list() -> Html {
         let refresh = refresh_list.clone();
         let index = index.clone();
         let selected_row = selected_row.clone();
-        let message = message.clone();
+        let toaster = toaster.clone();
         Callback::from(move |_| {
             let user_id = selected_row.borrow().clone().unwrap().id;
-            let message = message.clone();
+            let toaster = toaster.clone();
             spawn_local(async move {
                 match user_controller_api::delete(
                     &common::get_cli_config().unwrap(),
@@ -116,7 +126,7 @@ pub fn user_list() -> Html {
                 {
                     Ok(_) => {}
                     Err(err) => {
-                        *message.borrow_mut() = Some(message_list::error(&format!("{}", err)));
+                        toaster.error(&format!("{}", err));
                     }
                 }
             });
@@ -145,12 +155,12 @@ pub fn user_list() -> Html {
 
     let edit = {
         let selected_row = selected_row.clone();
-        let message = message.clone();
+        let toaster = toaster.clone();
         let user_form_closed = user_form_closed.clone();
         let force_update = force_update.clone();
         Callback::from(move |_e| {
             if selected_row.borrow().is_none() {
-                *message.borrow_mut() = Some(message_list::warn("please select a record"));
+                toaster.warn(&i18n::t("user.select_record"));
             } else {
                 *user_form_closed.borrow_mut() = false;
             }
@@ -160,12 +170,12 @@ pub fn user_list() -> Html {
 
     let delete = {
         let selected_row = selected_row.clone();
-        let message = message.clone();
+        let toaster = toaster.clone();
         let confirm_form_closed = confirm_form_closed.clone();
         let force_update = force_update.clone();
         Callback::from(move |_e| {
             if selected_row.borrow().is_none() {
-                *message.borrow_mut() = Some(message_list::warn("please select a record"));
+                toaster.warn(&i18n::t("user.select_record"));
             } else {
                 *confirm_form_closed.borrow_mut() = false;
             }
@@ -185,28 +195,29 @@ pub fn user_list() -> Html {
     };
 
     let selected_id = selected_row.borrow().clone().map(|x| x.id);
+    let keyword = searched.borrow().clone();
     html! {
     <>
-    <MessageList value = {(*message.borrow()).clone()} ws = true/>
+    <MessageList ws = true/>
     if let Some(v) = (*selected_row.clone().borrow()).clone()  {
         if !(*user_form_closed.borrow()){
             <UserForm value = {v.clone()} onclose={user_form_close} onupdate = {user_form_update}/>
         }
         if !(*confirm_form_closed.borrow()){
-            <ConfirmForm onclose = {confirm_form_close} onconfirm = {confirm_form_confirm.clone()} content = {"Deleted users <b>can not</b> be recovered!!!<br/> are you sure you want to delete it?"}/>
+            <ConfirmForm onclose = {confirm_form_close} onconfirm = {confirm_form_confirm.clone()} content = {i18n.t("user.delete_confirm")}/>
         }
     }
     <div class="search-container">
         <div class="search-input field is-grouped">
         <p class="control is-expanded">
-            <input ref={key_word_ref} class="input" type="text" onkeyup={key_word_change} placeholder="Search"/>
+            <input ref={key_word_ref} class="input" type="text" onkeyup={key_word_change} placeholder={i18n.t("common.search")}/>
         </p>
 
         <p class="control">
-            <button class="button is-light is-warning" onclick={edit}>{"Edit"}</button>
+            <button class="button is-light is-warning" onclick={edit}>{i18n.t("common.edit")}</button>
         </p>
         <p class="control">
-            <button class="button is-light is-danger" onclick={delete}>{"Delete"}</button>
+            <button class="button is-light is-danger" onclick={delete}>{i18n.t("common.delete")}</button>
         </p>
         </div>
     </div>
@@ -225,22 +236,21 @@ pub fn user_list() -> Html {
         <table class="table is-bordered is-striped is-narrow is-hoverable">
         <thead>
             <tr>
-            <th><abbr title="Type">{"Type"}</abbr></th>
-            <th><abbr title="Email">{"Email"}</abbr></th>
-            <th><abbr title="Name">{"Name"}</abbr></th>
-            <th><abbr title="Mobile">{"Mobile"}</abbr></th>
-            <th><abbr title="Laston">{"Laston"}</abbr></th>
+            <th>{i18n.t("user.type")}</th>
+            <th>{i18n.t("user.email")}</th>
+            <th>{i18n.t("user.name")}</th>
+            <th>{i18n.t("user.mobile")}</th>
+            <th>{i18n.t("user.laston")}</th>
             // todo: sort
-            <th><abbr title="Created_at"><a href="javascript:void(0)">{"Created_at  "}<i class="fa-solid fa-arrow-down"></i></a></abbr></th>
-            <th><abbr title="Updated_at">{"Updated_at"}</abbr></th>
-            <th><abbr title="Status">{"Status"}</abbr></th>
+            <th><a href="javascript:void(0)">{i18n.t("user.created_at")}{"  "}<i class="fa-solid fa-arrow-down"></i></a></th>
+            <th>{i18n.t("user.updated_at")}</th>
+            <th>{i18n.t("user.status")}</th>
             </tr>
         </thead>
         <tbody>
         {
             data.clone().borrow().iter().map(|x|{
                 let user = *(x.user.clone());
-                let formatter = *(x.formatter.clone());
                 let select_row = {
                     let selected_row = selected_row.clone();
                     let force_update = force_update.clone();
@@ -260,14 +270,28 @@ pub fn user_list() -> Html {
                 html! {
                     <tr class = {if is_selected {"is-selected"} else {""}}
                      onclick = {select_row} >
-                        {common::create_html("td",formatter.r#type.as_str())}
-                        {common::create_html("td",formatter.email.as_str())}
-                        {common::create_html("td",formatter.name.as_str())}
-                        {common::create_html("td",formatter.mobile.as_str())}
-                        {common::create_html("td",formatter.laston.as_str())}
-                        {common::create_html("td",formatter.created_at.as_str())}
-                        {common::create_html("td",formatter.updated_at.as_str())}
-                        {common::create_html("td",formatter.status.as_str())}
+                        <td>{highlight(&user.r#type.to_string(), &keyword)}</td>
+                        <td>{highlight(&user.email, &keyword)}</td>
+                        <td>{highlight(user.name.as_deref().unwrap_or_default(), &keyword)}</td>
+                        <td>{highlight(user.mobile.as_deref().unwrap_or_default(), &keyword)}</td>
+                        <td>
+                        {
+                            match user.laston {
+                                Some(v) => html!{ <Timestamp value={v}/> },
+                                None => html!{ {i18n.t("time.never")} },
+                            }
+                        }
+                        </td>
+                        <td><Timestamp value={user.created_at} relative={false}/></td>
+                        <td>
+                        {
+                            match user.updated_at {
+                                Some(v) => html!{ <Timestamp value={v}/> },
+                                None => html!{},
+                            }
+                        }
+                        </td>
+                        <td>{highlight(&user.status.to_string(), &keyword)}</td>
                     </tr>
                 }
         }).collect::<Html>()
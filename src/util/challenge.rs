@@ -0,0 +1,310 @@
+use crate::util::common::BasicResult;
+use crate::util::error::{ErrorKind, ToError};
+use crate::util::form::{self, use_form, Field, InputField};
+use crate::util::i18n::{self, use_translation};
+use crate::util::request::{self, Host};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::cell::Cell;
+use std::rc::Rc;
+use wasm_bindgen_futures::{spawn_local, JsFuture};
+use yew::prelude::*;
+
+// hashes between two yields to the browser, keeps the page responsive
+const POW_BATCH: u64 = 2000;
+// about 16 million hashes on average, harder ones are refused instead of
+// keeping the tab busy
+const MAX_POW_DIFFICULTY: u32 = 24;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ChallengePurpose {
+    SendCode,
+    Login,
+}
+
+impl ChallengePurpose {
+    fn code(&self) -> &'static str {
+        match self {
+            ChallengePurpose::SendCode => "send_code",
+            ChallengePurpose::Login => "login",
+        }
+    }
+}
+
+// the server picks the kind, both are answered through verify
+#[derive(Deserialize, Clone, PartialEq, Debug)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Challenge {
+    // the answer is a nonce, sha256 of prefix + nonce starts with difficulty zero bits
+    Pow {
+        id: String,
+        prefix: String,
+        difficulty: u32,
+    },
+    // the answer is the text of the image, a data: url
+    Captcha {
+        id: String,
+        image: String,
+    },
+}
+
+#[derive(Serialize)]
+struct VerifyReq<'a> {
+    id: &'a str,
+    answer: &'a str,
+}
+
+// what a protected request may go on with
+#[derive(Clone, PartialEq, Debug)]
+pub enum Pass {
+    // the server asks for no challenge right now
+    NotRequired,
+    // single use, attached to the protected request
+    Token(String),
+}
+
+impl Pass {
+    pub fn token(&self) -> Option<String> {
+        match self {
+            Pass::NotRequired => None,
+            Pass::Token(v) => Some(v.clone()),
+        }
+    }
+}
+
+// None when no challenge is required for the purpose, a server without the
+// route requires none either
+pub async fn fetch(purpose: ChallengePurpose) -> BasicResult<Option<Challenge>> {
+    match request::get::<Challenge, _, _>(
+        Host::AxumBase,
+        "/auth/challenge",
+        Some(vec![("purpose", purpose.code())]),
+    )
+    .await
+    {
+        Ok(res) => Ok(res.data),
+        Err(ErrorKind::Status(404, _)) => Ok(None),
+        Err(err) => Err(err),
+    }
+}
+
+pub async fn verify(id: &str, answer: &str) -> BasicResult<String> {
+    request::post::<String, _>(
+        Host::AxumBase,
+        "/auth/challenge/verify",
+        &VerifyReq { id, answer },
+    )
+    .await?
+    .data
+    .ok_or_else(|| ErrorKind::OtherError(String::from("challenge verify returned no token")))
+}
+
+fn leading_zero_bits(hash: &[u8]) -> u32 {
+    let mut bits = 0;
+    for b in hash {
+        if *b != 0 {
+            return bits + b.leading_zeros();
+        }
+        bits += 8;
+    }
+    bits
+}
+
+// lets the browser render and handle events between two batches
+async fn yield_now() {
+    let promise = js_sys::Promise::new(&mut |resolve, _| {
+        let scheduled = web_sys::window()
+            .map(|x| x.set_timeout_with_callback(&resolve).is_ok())
+            .unwrap_or_default();
+        if !scheduled {
+            let _ = resolve.call0(&wasm_bindgen::JsValue::NULL);
+        }
+    });
+    let _ = JsFuture::from(promise).await;
+}
+
+// about 2^difficulty hashes on average, None once cancelled
+pub async fn solve_pow(prefix: &str, difficulty: u32, cancelled: &Cell<bool>) -> Option<u64> {
+    let mut nonce = 0u64;
+    loop {
+        for _ in 0..POW_BATCH {
+            let hash = Sha256::digest(format!("{}{}", prefix, nonce).as_bytes());
+            if leading_zero_bits(&hash) >= difficulty {
+                return Some(nonce);
+            }
+            nonce += 1;
+        }
+        yield_now().await;
+        if cancelled.get() {
+            return None;
+        }
+    }
+}
+
+#[derive(Clone, PartialEq)]
+enum State {
+    Loading,
+    // nothing to show, the server asked for no challenge
+    NotRequired,
+    Solving,
+    Captcha { id: String, image: String },
+    Passed,
+    Failed(String),
+}
+
+#[derive(PartialEq, Properties)]
+pub struct ChallengeFieldProps {
+    pub purpose: ChallengePurpose,
+    // None until the challenge is passed, the parent remounts the field with
+    // a new key after the pass was used
+    pub onpass: Callback<Option<Pass>>,
+}
+
+// asks the server for a challenge on mount, solves proof of work on its own
+// and asks the user only for a captcha
+#[function_component(ChallengeField)]
+pub fn challenge_field(props: &ChallengeFieldProps) -> Html {
+    let i18n = use_translation();
+    let state = use_state(|| State::Loading);
+    let form = use_form(|| vec![Field::new("answer").validate(form::required())]);
+    // bumped to ask for another challenge
+    let round = use_state(|| 0u32);
+
+    {
+        let state = state.clone();
+        let purpose = props.purpose;
+        let onpass = props.onpass.clone();
+        use_effect_with(*round, move |_| {
+            state.set(State::Loading);
+            onpass.emit(None);
+            // set on unmount and on the next round, the work is dropped
+            let cancelled = Rc::new(Cell::new(false));
+            let cleanup = {
+                let cancelled = cancelled.clone();
+                move || cancelled.set(true)
+            };
+            spawn_local(async move {
+                let res = match fetch(purpose).await {
+                    Ok(None) => Ok(Some(Pass::NotRequired)),
+                    Ok(Some(Challenge::Captcha { id, image })) => {
+                        state.set(State::Captcha { id, image });
+                        Ok(None)
+                    }
+                    Ok(Some(Challenge::Pow { difficulty, .. }))
+                        if difficulty > MAX_POW_DIFFICULTY =>
+                    {
+                        Err(i18n::t("challenge.too_hard").to_basic_error())
+                    }
+                    Ok(Some(Challenge::Pow {
+                        id,
+                        prefix,
+                        difficulty,
+                    })) => {
+                        state.set(State::Solving);
+                        match solve_pow(&prefix, difficulty, &cancelled).await {
+                            Some(nonce) => verify(&id, &nonce.to_string())
+                                .await
+                                .map(|x| Some(Pass::Token(x))),
+                            None => return,
+                        }
+                    }
+                    Err(err) => Err(err),
+                };
+                if cancelled.get() {
+                    return;
+                }
+                match res {
+                    Ok(Some(Pass::NotRequired)) => {
+                        state.set(State::NotRequired);
+                        onpass.emit(Some(Pass::NotRequired));
+                    }
+                    Ok(Some(pass)) => {
+                        state.set(State::Passed);
+                        onpass.emit(Some(pass));
+                    }
+                    Ok(None) => {}
+                    Err(err) => state.set(State::Failed(err.to_string())),
+                }
+            });
+            cleanup
+        });
+    }
+
+    let on_refresh = {
+        let form = form.clone();
+        let round = round.clone();
+        Callback::from(move |_: MouseEvent| {
+            form.reset();
+            round.set(*round + 1);
+        })
+    };
+
+    let on_verify = {
+        let state = state.clone();
+        let form = form.clone();
+        let round = round.clone();
+        let onpass = props.onpass.clone();
+        Callback::from(move |_| {
+            let id = match &*state {
+                State::Captcha { id, .. } if form.touch_all() => id.clone(),
+                _ => return,
+            };
+            let answer = form.value("answer").trim().to_string();
+            let state = state.clone();
+            let form = form.clone();
+            let round = round.clone();
+            let onpass = onpass.clone();
+            spawn_local(async move {
+                match verify(&id, &answer).await {
+                    Ok(token) => {
+                        state.set(State::Passed);
+                        onpass.emit(Some(Pass::Token(token)));
+                    }
+                    // a captcha is answered once, a new one is shown
+                    Err(err) => {
+                        form.reset();
+                        form.set_error("answer", &err.to_string());
+                        round.set(*round + 1);
+                    }
+                }
+            });
+        })
+    };
+
+    let refresh = html! {
+        <button class="button" title={i18n.t("challenge.refresh")} onclick={on_refresh}>
+            <span class="icon"><i class="fa-solid fa-rotate"></i></span>
+        </button>
+    };
+    match &*state {
+        State::Loading | State::NotRequired => html! {},
+        State::Solving => html! {
+            <p class="help challenge">
+                <span class="icon is-small"><i class="fa-solid fa-spinner fa-spin"></i></span>
+                <span>{i18n.t("challenge.solving")}</span>
+            </p>
+        },
+        State::Passed => html! {
+            <p class="help challenge has-text-success">
+                <span class="icon is-small"><i class="fa-solid fa-check"></i></span>
+                <span>{i18n.t("challenge.passed")}</span>
+            </p>
+        },
+        State::Failed(msg) => html! {
+            <div class="field challenge">
+                <p class="help is-danger">{msg.clone()}</p>
+                {refresh}
+            </div>
+        },
+        State::Captcha { image, .. } => html! {
+            <div class="challenge">
+                <img class="challenge-image" src={image.clone()} alt={i18n.t("challenge.captcha")}/>
+                <InputField form={form.clone()} name="answer" placeholder={i18n.t("challenge.captcha")}
+                    autocomplete="off" icon="fa-solid fa-robot" idle="" onenter={on_verify.clone()} addon={refresh}/>
+                <button class="button is-small" disabled={!form.is_valid("answer")} onclick={on_verify.reform(|_| ())}>
+                    {i18n.t("challenge.verify")}
+                </button>
+            </div>
+        },
+    }
+}
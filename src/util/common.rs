@@ -1,52 +1,59 @@
 use crate::util::error::ErrorKind;
 use crate::util::error::ToError;
+use crate::util::i18n;
+use crate::util::password;
 use evolve_axum_cli::apis::configuration::{ApiKey, Configuration};
 use fancy_regex::Regex;
 use serde::{Deserialize, Serialize};
 use serde_json;
-use yew::virtual_dom::VNode;
+use yew::UseStateHandle;
 
 pub type BasicResult<T, E = ErrorKind> = Result<T, E>;
 
-const BASE_URL: &str = "http://localhost:3000";
+pub const BASE_URL: &str = "http://localhost:3000";
 
 pub fn validate_email(email: &str) -> BasicResult<()> {
     if email.is_empty() {
-        return Err("please type in email".to_validation_error());
+        return Err(i18n::t("validate.email.empty").to_validation_error());
     }
     let reg = Regex::new(r#"\w[-\w.+]*@([A-Za-z0-9][-A-Za-z0-9]+\.)+[A-Za-z]{2,14}"#)?;
     if !reg.is_match(email)? {
-        return Err("invalid email".to_validation_error());
+        return Err(i18n::t("validate.email.invalid").to_validation_error());
     }
     Ok(())
 }
 
+// checked against the password policy of the server, see util::password
 pub fn validate_pwd(pwd: &str) -> BasicResult<()> {
-    if pwd.is_empty() {
-        return Err("please type in password".to_validation_error());
-    }
-    let reg = Regex::new(r#"(?=.*[a-z])(?=.*[0-9])[a-zA-Z0-9]{6,18}"#)?; //6位字母+数字,字母开头
-    if !reg.is_match(pwd)? {
-        return Err("invalid passowrd: length>=6, a-z and 0-9 is demanded".to_validation_error());
-    }
-    Ok(())
+    password::policy().validate(pwd)
 }
 
 pub fn validate_pwd_confirm(pwd: &str, pwd_confirm: &str) -> BasicResult<()> {
     validate_pwd(pwd_confirm)?;
     if pwd != pwd_confirm {
-        return Err("confirm password must as same as password".to_validation_error());
+        return Err(i18n::t("validate.pwd_confirm.mismatch").to_validation_error());
     }
     Ok(())
 }
 
 pub fn validate_code(code: &str) -> BasicResult<()> {
     if code.is_empty() {
-        return Err("please type in code".to_validation_error());
+        return Err(i18n::t("validate.code.empty").to_validation_error());
     }
-    let reg = Regex::new(r#"^\d{6}$"#)?; //6位字母+数字,字母开头
+    let reg = Regex::new(r#"^\d{6}$"#)?; //6位数字
     if !reg.is_match(code)? {
-        return Err("invalid code: length=6 and 0-9 is demanded".to_validation_error());
+        return Err(i18n::t("validate.code.invalid").to_validation_error());
+    }
+    Ok(())
+}
+
+pub fn validate_mobile(mobile: &str) -> BasicResult<()> {
+    if mobile.is_empty() {
+        return Err(i18n::t("validate.mobile.empty").to_validation_error());
+    }
+    let reg = Regex::new(r#"^\+?[0-9][0-9 -]{4,18}[0-9]$"#)?;
+    if !reg.is_match(mobile)? {
+        return Err(i18n::t("validate.mobile.invalid").to_validation_error());
     }
     Ok(())
 }
@@ -61,8 +68,14 @@ pub struct CurrentUser {
     pub laston: Option<i64>,
     pub created_at: i64,
     pub updated_at: Option<i64>,
+    #[serde(default)]
+    pub avatar: Option<String>,
 }
 
+// provided by the layout, pages that change the signed-in user set it so
+// that the header shows the change at once
+pub type CurrentUserHandle = UseStateHandle<Option<CurrentUser>>;
+
 pub fn get_token() -> BasicResult<String> {
     let str = get_local_storage(crate::util::TOKEN_KEY)
         .ok_or(ErrorKind::OtherError(String::from("get token failed")))?;
@@ -93,6 +106,11 @@ pub fn get_current_user() -> BasicResult<CurrentUser> {
     Ok(res)
 }
 
+pub fn set_current_user(user: &CurrentUser) -> BasicResult<()> {
+    set_local_storage("current_user", &serde_json::to_string(user)?);
+    Ok(())
+}
+
 pub fn delete_current_user() -> BasicResult<()> {
     del_local_storage(crate::util::TOKEN_KEY);
     del_local_storage("current_user");
@@ -124,21 +142,8 @@ pub fn redirect(path: &str) {
         .unwrap();
 }
 
-pub fn create_html(tag: &str, inner_html: &str) -> VNode {
-    let element = web_sys::window()
-        .unwrap()
-        .document()
-        .unwrap()
-        .create_element(tag)
-        .unwrap();
-    element.set_inner_html(inner_html);
-    let vnode = yew::virtual_dom::VNode::VRef(web_sys::Node::from(element));
-
-    vnode
-}
-
 #[allow(dead_code)]
-#[derive(PartialEq)]
+#[derive(PartialEq, Clone, Debug)]
 pub enum ValidStatus {
     Valid,
     InValid(String),
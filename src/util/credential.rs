@@ -0,0 +1,83 @@
+use crate::util::common;
+use js_sys::{Array, Function, Object, Reflect};
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{Credential, CredentialRequestOptions, CredentialsContainer};
+
+// the only thing "remember me" keeps, the login page prefills it
+pub const REMEMBERED_EMAIL_KEY: &str = "email";
+// older versions kept the password in plaintext under this key
+const LEGACY_PWD_KEY: &str = "pwd";
+
+// run once at startup, removes passwords left by older versions
+pub fn purge_legacy() {
+    if common::get_local_storage(LEGACY_PWD_KEY).is_some() {
+        common::del_local_storage(LEGACY_PWD_KEY);
+    }
+}
+
+pub fn remembered_email() -> Option<String> {
+    common::get_local_storage(REMEMBERED_EMAIL_KEY).filter(|x| !x.is_empty())
+}
+
+pub fn remember_email(email: Option<&str>) {
+    match email {
+        Some(email) => common::set_local_storage(REMEMBERED_EMAIL_KEY, email),
+        None => common::del_local_storage(REMEMBERED_EMAIL_KEY),
+    }
+}
+
+// PasswordCredential is not available in every browser, e.g. firefox and safari
+fn password_credential() -> Option<(CredentialsContainer, Function)> {
+    let window = web_sys::window()?;
+    let class = Reflect::get(&window, &JsValue::from_str("PasswordCredential"))
+        .ok()?
+        .dyn_into::<Function>()
+        .ok()?;
+    Some((window.navigator().credentials(), class))
+}
+
+// offers the browser password manager to save the credential, the password
+// never touches our own storage
+pub async fn store(email: &str, pwd: &str) {
+    let (container, class) = match password_credential() {
+        Some(v) => v,
+        None => return,
+    };
+    let data = Object::new();
+    let _ = Reflect::set(&data, &"id".into(), &email.into());
+    let _ = Reflect::set(&data, &"password".into(), &pwd.into());
+    let credential = match Reflect::construct(&class, &Array::of1(&data)) {
+        Ok(v) => v.unchecked_into::<Credential>(),
+        Err(err) => return log::warn!("create password credential error: {:?}", err),
+    };
+    if let Ok(promise) = container.store(&credential) {
+        if let Err(err) = JsFuture::from(promise).await {
+            log::warn!("store password credential error: {:?}", err);
+        }
+    }
+}
+
+// the credential saved by the browser, only returned without asking the user,
+// None if there is none or the user signed out
+pub async fn get() -> Option<(String, String)> {
+    let (container, _) = password_credential()?;
+    let options = CredentialRequestOptions::new();
+    let _ = Reflect::set(&options, &"password".into(), &JsValue::TRUE);
+    let _ = Reflect::set(&options, &"mediation".into(), &"silent".into());
+    let credential = JsFuture::from(container.get_with_options(&options).ok()?)
+        .await
+        .ok()?;
+    let id = Reflect::get(&credential, &"id".into()).ok()?.as_string()?;
+    let pwd = Reflect::get(&credential, &"password".into())
+        .ok()?
+        .as_string()?;
+    Some((id, pwd))
+}
+
+// after logout the browser must not hand out the credential silently
+pub fn prevent_silent_access() {
+    if let Some((container, _)) = password_credential() {
+        let _ = container.prevent_silent_access();
+    }
+}
@@ -6,6 +6,8 @@ pub enum ErrorKind {
     ValidationError(String),
     ServerError(String),
     Hint(String),
+    // a 401 or 404 handed to the caller instead of the error pages
+    Status(u16, String),
     OtherError(String),
 }
 
@@ -18,6 +20,7 @@ impl<'a> Display for ErrorKind {
             }
             ErrorKind::ServerError(msg) => f.write_str(msg),
             ErrorKind::Hint(msg) => f.write_str(msg),
+            ErrorKind::Status(_, msg) => f.write_str(msg),
             ErrorKind::OtherError(msg) => f.write_fmt(format_args!("other error: {}", msg)),
         }
     }
@@ -43,6 +46,12 @@ impl From<gloo_net::Error> for ErrorKind {
     }
 }
 
+impl From<wasm_bindgen::JsValue> for ErrorKind {
+    fn from(err: wasm_bindgen::JsValue) -> Self {
+        ErrorKind::OtherError(format!("{:?}", err))
+    }
+}
+
 pub trait ToError {
     fn to_basic_error(&self) -> ErrorKind;
     fn to_validation_error(&self) -> ErrorKind;
@@ -0,0 +1,530 @@
+use crate::util::common::{self, BasicResult, ValidStatus};
+use crate::util::error::ToError;
+use crate::util::i18n;
+use futures::future::LocalBoxFuture;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::future::Future;
+use std::rc::Rc;
+use wasm_bindgen_futures::spawn_local;
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+use yew::Properties;
+
+pub type Values = HashMap<&'static str, String>;
+// gets the value of the field and the values of all fields
+pub type Validator = Rc<dyn Fn(&str, &Values) -> BasicResult<()>>;
+// runs after the sync validators passed, e.g. asks the server
+pub type AsyncValidator = Rc<dyn Fn(String) -> LocalBoxFuture<'static, BasicResult<()>>>;
+
+pub fn validator(f: impl Fn(&str) -> BasicResult<()> + 'static) -> Validator {
+    Rc::new(move |value, _| f(value))
+}
+
+pub fn async_validator<F, Fut>(f: F) -> AsyncValidator
+where
+    F: Fn(String) -> Fut + 'static,
+    Fut: Future<Output = BasicResult<()>> + 'static,
+{
+    Rc::new(move |value| Box::pin(f(value)))
+}
+
+pub fn required() -> Validator {
+    validator(|value| match value.trim().is_empty() {
+        true => Err(i18n::t("validate.required").to_validation_error()),
+        false => Ok(()),
+    })
+}
+
+pub fn email() -> Validator {
+    validator(common::validate_email)
+}
+
+pub fn pwd() -> Validator {
+    validator(common::validate_pwd)
+}
+
+pub fn code() -> Validator {
+    validator(common::validate_code)
+}
+
+pub fn mobile() -> Validator {
+    validator(common::validate_mobile)
+}
+
+// the confirmation of the password in field pwd_field
+pub fn pwd_confirm(pwd_field: &'static str) -> Validator {
+    Rc::new(move |value, values| {
+        common::validate_pwd_confirm(values.get(pwd_field).map_or("", |x| x), value)
+    })
+}
+
+pub fn max_len(len: usize) -> Validator {
+    validator(move |value| match value.chars().count() > len {
+        true => Err(i18n::t_with("validate.max_len", &[("len", &len)]).to_validation_error()),
+        false => Ok(()),
+    })
+}
+
+// empty values pass, otherwise the inner validator decides
+pub fn optional(inner: Validator) -> Validator {
+    Rc::new(move |value, values| match value.is_empty() {
+        true => Ok(()),
+        false => inner(value, values),
+    })
+}
+
+pub struct Field {
+    name: &'static str,
+    initial: String,
+    validators: Vec<Validator>,
+    async_validator: Option<AsyncValidator>,
+}
+
+impl Field {
+    pub fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            initial: String::default(),
+            validators: vec![],
+            async_validator: None,
+        }
+    }
+
+    pub fn initial(mut self, value: &str) -> Self {
+        self.initial = value.to_string();
+        self
+    }
+
+    pub fn validate(mut self, validator: Validator) -> Self {
+        self.validators.push(validator);
+        self
+    }
+
+    pub fn validate_async(mut self, validator: AsyncValidator) -> Self {
+        self.async_validator = Some(validator);
+        self
+    }
+}
+
+const NAME_MAX_LEN: usize = 32;
+
+// the details a user may edit, shared by the user form and the profile page
+pub fn user_details(name: Option<&str>, mobile: Option<&str>) -> Vec<Field> {
+    vec![
+        Field::new("name")
+            .initial(name.unwrap_or_default())
+            .validate(optional(max_len(NAME_MAX_LEN))),
+        Field::new("mobile")
+            .initial(mobile.unwrap_or_default())
+            .validate(optional(self::mobile())),
+    ]
+}
+
+struct FieldState {
+    value: String,
+    touched: bool,
+    // None while the async validator runs
+    status: ValidStatus,
+    // results of older async runs are ignored
+    generation: u32,
+}
+
+struct FormState {
+    fields: Vec<Field>,
+    states: HashMap<&'static str, FieldState>,
+    version: u32,
+}
+
+impl FormState {
+    fn new(fields: Vec<Field>) -> Self {
+        let states = fields
+            .iter()
+            .map(|x| {
+                let state = FieldState {
+                    value: x.initial.clone(),
+                    touched: false,
+                    status: ValidStatus::None,
+                    generation: 0,
+                };
+                (x.name, state)
+            })
+            .collect();
+        let mut res = Self {
+            fields,
+            states,
+            version: 0,
+        };
+        // initial values are not checked by the async validators
+        for name in res.fields.iter().map(|x| x.name).collect::<Vec<_>>() {
+            res.validate_sync(name);
+        }
+        res
+    }
+
+    fn values(&self) -> Values {
+        self.states
+            .iter()
+            .map(|(name, state)| (*name, state.value.clone()))
+            .collect()
+    }
+
+    fn validate_sync(&mut self, name: &'static str) -> bool {
+        let values = self.values();
+        let field = match self.fields.iter().find(|x| x.name == name) {
+            Some(v) => v,
+            None => return false,
+        };
+        let value = values.get(name).cloned().unwrap_or_default();
+        let res = field.validators.iter().try_for_each(|x| x(&value, &values));
+        if let Some(state) = self.states.get_mut(name) {
+            state.status = match res {
+                Ok(_) => ValidStatus::Valid,
+                Err(e) => ValidStatus::InValid(e.to_string()),
+            };
+            return state.status == ValidStatus::Valid;
+        }
+        false
+    }
+}
+
+// a snapshot of the form for one render, cheap to clone
+#[derive(Clone)]
+pub struct FormHandle {
+    state: Rc<RefCell<FormState>>,
+    version: u32,
+    refresh: Callback<()>,
+}
+
+// fields render again whenever the form changed
+impl PartialEq for FormHandle {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.state, &other.state) && self.version == other.version
+    }
+}
+
+impl FormHandle {
+    fn changed(&self) {
+        self.state.borrow_mut().version += 1;
+        self.refresh.emit(());
+    }
+
+    pub fn value(&self, name: &str) -> String {
+        self.state
+            .borrow()
+            .states
+            .get(name)
+            .map(|x| x.value.clone())
+            .unwrap_or_default()
+    }
+
+    pub fn values(&self) -> Values {
+        self.state.borrow().values()
+    }
+
+    pub fn status(&self, name: &str) -> ValidStatus {
+        self.state
+            .borrow()
+            .states
+            .get(name)
+            .map(|x| x.status.clone())
+            .unwrap_or_default()
+    }
+
+    pub fn is_valid(&self, name: &str) -> bool {
+        self.status(name) == ValidStatus::Valid
+    }
+
+    pub fn is_touched(&self, name: &str) -> bool {
+        self.state
+            .borrow()
+            .states
+            .get(name)
+            .map(|x| x.touched)
+            .unwrap_or_default()
+    }
+
+    pub fn is_dirty(&self, name: &str) -> bool {
+        let borrow = self.state.borrow();
+        let initial = borrow
+            .fields
+            .iter()
+            .find(|x| x.name == name)
+            .map(|x| &x.initial);
+        let value = borrow.states.get(name).map(|x| &x.value);
+        initial != value
+    }
+
+    pub fn is_form_dirty(&self) -> bool {
+        let names = self
+            .state
+            .borrow()
+            .fields
+            .iter()
+            .map(|x| x.name)
+            .collect::<Vec<_>>();
+        names.into_iter().any(|x| self.is_dirty(x))
+    }
+
+    pub fn is_pending(&self) -> bool {
+        self.state
+            .borrow()
+            .states
+            .values()
+            .any(|x| x.status == ValidStatus::None)
+    }
+
+    // every field passed its validators and no async validator is running
+    pub fn can_submit(&self) -> bool {
+        self.state
+            .borrow()
+            .states
+            .values()
+            .all(|x| x.status == ValidStatus::Valid)
+    }
+
+    // sync validators of all fields run again, e.g. the confirmation when the password changed
+    pub fn set_value(&self, name: &'static str, value: String) {
+        {
+            let mut borrow = self.state.borrow_mut();
+            match borrow.states.get_mut(name) {
+                Some(state) if state.value != value => {
+                    state.value = value;
+                    state.touched = true;
+                }
+                _ => return,
+            }
+            // results of async validators stay as they are
+            let names = borrow
+                .fields
+                .iter()
+                .filter(|x| x.name != name && x.async_validator.is_none())
+                .map(|x| x.name)
+                .collect::<Vec<_>>();
+            for x in names {
+                borrow.validate_sync(x);
+            }
+        }
+        self.validate(name);
+    }
+
+    // sync validators run again without touching the fields, e.g. after the
+    // rules they read changed
+    pub fn revalidate(&self, names: &[&'static str]) {
+        {
+            let mut borrow = self.state.borrow_mut();
+            for name in names {
+                borrow.validate_sync(*name);
+            }
+        }
+        self.changed();
+    }
+
+    // shows the messages of a field that was left without typing
+    pub fn touch(&self, name: &'static str) {
+        {
+            let mut borrow = self.state.borrow_mut();
+            match borrow.states.get_mut(name) {
+                Some(state) if !state.touched => state.touched = true,
+                _ => return,
+            }
+        }
+        self.changed();
+    }
+
+    // e.g. errors returned by the server for this field
+    pub fn set_error(&self, name: &'static str, msg: &str) {
+        if let Some(state) = self.state.borrow_mut().states.get_mut(name) {
+            state.touched = true;
+            state.status = ValidStatus::InValid(msg.to_string());
+        }
+        self.changed();
+    }
+
+    // back to the initial values, e.g. after the form was submitted
+    pub fn reset(&self) {
+        {
+            let mut borrow = self.state.borrow_mut();
+            let fields = borrow
+                .fields
+                .iter()
+                .map(|x| (x.name, x.initial.clone()))
+                .collect::<Vec<_>>();
+            for (name, initial) in fields {
+                if let Some(state) = borrow.states.get_mut(name) {
+                    state.value = initial;
+                    state.touched = false;
+                    // a running async validator must not overwrite the reset
+                    state.generation += 1;
+                }
+                borrow.validate_sync(name);
+            }
+        }
+        self.changed();
+    }
+
+    // touches all fields so that their messages are shown, true if the form can be submitted
+    pub fn touch_all(&self) -> bool {
+        for state in self.state.borrow_mut().states.values_mut() {
+            state.touched = true;
+        }
+        self.changed();
+        self.can_submit()
+    }
+
+    fn validate(&self, name: &'static str) {
+        let (valid, async_validator) = {
+            let mut borrow = self.state.borrow_mut();
+            let valid = borrow.validate_sync(name);
+            let async_validator = borrow
+                .fields
+                .iter()
+                .find(|x| x.name == name)
+                .and_then(|x| x.async_validator.clone());
+            (valid, async_validator)
+        };
+        if let (true, Some(async_validator)) = (valid, async_validator) {
+            let (value, generation) = {
+                let mut borrow = self.state.borrow_mut();
+                let state = borrow.states.get_mut(name).unwrap();
+                state.status = ValidStatus::None;
+                state.generation += 1;
+                (state.value.clone(), state.generation)
+            };
+            let handle = self.clone();
+            spawn_local(async move {
+                let res = async_validator(value).await;
+                {
+                    let mut borrow = handle.state.borrow_mut();
+                    match borrow.states.get_mut(name) {
+                        Some(state) if state.generation == generation => {
+                            state.status = match res {
+                                Ok(_) => ValidStatus::Valid,
+                                Err(e) => ValidStatus::InValid(e.to_string()),
+                            };
+                        }
+                        _ => return,
+                    }
+                }
+                handle.changed();
+            });
+        }
+        self.changed();
+    }
+}
+
+#[hook]
+pub fn use_form<F>(fields: F) -> FormHandle
+where
+    F: FnOnce() -> Vec<Field>,
+{
+    let force_update = use_force_update();
+    let state = use_mut_ref(|| FormState::new(fields()));
+    let version = state.borrow().version;
+    FormHandle {
+        state,
+        version,
+        refresh: Callback::from(move |_| force_update.force_update()),
+    }
+}
+
+#[derive(PartialEq, Properties)]
+pub struct InputFieldProps {
+    pub form: FormHandle,
+    pub name: &'static str,
+    #[prop_or_default]
+    pub label: Option<AttrValue>,
+    #[prop_or(AttrValue::Static("text"))]
+    pub input_type: AttrValue,
+    #[prop_or_default]
+    pub placeholder: AttrValue,
+    // hints for password managers, e.g. "username", "current-password"
+    #[prop_or_default]
+    pub autocomplete: Option<AttrValue>,
+    // font awesome classes of the icon on the left
+    #[prop_or_default]
+    pub icon: Option<AttrValue>,
+    // added to the classes of the input, e.g. "is-medium is-rounded"
+    #[prop_or_default]
+    pub class: Classes,
+    // color of the input before it was touched
+    #[prop_or(AttrValue::Static("is-info"))]
+    pub idle: AttrValue,
+    #[prop_or_default]
+    pub disabled: bool,
+    #[prop_or_default]
+    pub autofocus: bool,
+    #[prop_or_default]
+    pub onenter: Callback<()>,
+    // shown next to the input, e.g. a button
+    #[prop_or_default]
+    pub addon: Option<Html>,
+}
+
+#[function_component(InputField)]
+pub fn input_field(props: &InputFieldProps) -> Html {
+    let input_ref = use_node_ref();
+    let name = props.name;
+
+    {
+        let input_ref = input_ref.clone();
+        use_effect_with(props.autofocus, move |autofocus| {
+            if *autofocus {
+                if let Some(el) = input_ref.cast::<HtmlInputElement>() {
+                    el.focus().unwrap_or_default();
+                }
+            }
+        });
+    }
+
+    let oninput = {
+        let form = props.form.clone();
+        Callback::from(move |e: InputEvent| {
+            let el: HtmlInputElement = e.target_unchecked_into();
+            form.set_value(name, el.value());
+        })
+    };
+    let onblur = {
+        let form = props.form.clone();
+        Callback::from(move |_| form.touch(name))
+    };
+    let onkeydown = {
+        let onenter = props.onenter.clone();
+        Callback::from(move |e: KeyboardEvent| {
+            if e.key() == "Enter" {
+                onenter.emit(());
+            }
+        })
+    };
+
+    let status = props.form.status(name);
+    let touched = props.form.is_touched(name);
+    let pending = status == ValidStatus::None;
+    let (color, msg) = match (&status, touched) {
+        (ValidStatus::Valid, true) => (AttrValue::Static("is-success"), String::default()),
+        (ValidStatus::InValid(msg), true) => (AttrValue::Static("is-danger"), msg.clone()),
+        _ => (props.idle.clone(), String::default()),
+    };
+
+    html! {
+        <div class="field">
+            if let Some(label) = &props.label {
+                <label class="label">{label}</label>
+            }
+            <div class={classes!("field", "mb-0", props.addon.is_some().then_some("has-addons"))}>
+                <div class={classes!("control", "is-expanded", props.icon.is_some().then_some("has-icons-left"), pending.then_some("is-loading"))}>
+                    <input ref={input_ref} class={classes!("input", color.to_string(), props.class.clone())} type={props.input_type.clone()}
+                        value={props.form.value(name)} placeholder={props.placeholder.clone()} autocomplete={props.autocomplete.clone()} disabled={props.disabled}
+                        {oninput} {onblur} {onkeydown}/>
+                    if let Some(icon) = &props.icon {
+                        <span class="icon is-small is-left"><i class={icon.to_string()}></i></span>
+                    }
+                </div>
+                if let Some(addon) = &props.addon {
+                    <div class="control">{addon.clone()}</div>
+                }
+            </div>
+            <p class="help is-danger">{msg}</p>
+        </div>
+    }
+}
@@ -0,0 +1,57 @@
+use unicode_normalization::char::{decompose_canonical, is_combining_mark};
+use yew::prelude::*;
+
+// lower case without accents, e.g. 'É' -> "e"
+fn fold(c: char) -> Vec<char> {
+    let mut res = vec![];
+    decompose_canonical(c, |x| {
+        if !is_combining_mark(x) {
+            res.extend(x.to_lowercase());
+        }
+    });
+    res
+}
+
+// byte ranges of the keyword in text, case and diacritic insensitive
+pub fn find_matches(text: &str, keyword: &str) -> Vec<(usize, usize)> {
+    let keyword = keyword.trim().chars().flat_map(fold).collect::<Vec<char>>();
+    if keyword.is_empty() {
+        return vec![];
+    }
+    // every folded char remembers the byte range of the char it comes from
+    let mut folded: Vec<(char, usize, usize)> = vec![];
+    for (start, c) in text.char_indices() {
+        let end = start + c.len_utf8();
+        folded.extend(fold(c).into_iter().map(|x| (x, start, end)));
+    }
+    let mut res: Vec<(usize, usize)> = vec![];
+    let mut i = 0;
+    while i + keyword.len() <= folded.len() {
+        let window = &folded[i..i + keyword.len()];
+        if window.iter().map(|x| x.0).eq(keyword.iter().copied()) {
+            let (start, end) = (window[0].1, window[window.len() - 1].2);
+            match res.last_mut() {
+                // one original char may fold into several, do not split it
+                Some(last) if start < last.1 => last.1 = last.1.max(end),
+                _ => res.push((start, end)),
+            }
+            i += keyword.len();
+        } else {
+            i += 1;
+        }
+    }
+    res
+}
+
+// plain text with the matches wrapped in <span class="highlight">
+pub fn highlight(text: &str, keyword: &str) -> Html {
+    let mut last = 0;
+    let mut res = vec![];
+    for (start, end) in find_matches(text, keyword) {
+        res.push(html! { {text[last..start].to_string()} });
+        res.push(html! { <span class="highlight">{text[start..end].to_string()}</span> });
+        last = end;
+    }
+    res.push(html! { {text[last..].to_string()} });
+    res.into_iter().collect::<Html>()
+}
@@ -0,0 +1,227 @@
+use crate::util::common;
+use crate::util::sync;
+use chrono::{DateTime, TimeZone};
+use lazy_static::lazy_static;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt::Display;
+use yew::prelude::*;
+
+pub const LOCALE_KEY: &str = "locale";
+
+type Catalog = HashMap<String, String>;
+
+lazy_static! {
+    static ref EN: Catalog = serde_json::from_str(include_str!("../../locales/en.json")).unwrap();
+    static ref ZH_CN: Catalog =
+        serde_json::from_str(include_str!("../../locales/zh-CN.json")).unwrap();
+}
+
+thread_local! {
+    static CURRENT: RefCell<Option<Locale>> = RefCell::new(None);
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum Locale {
+    #[default]
+    En,
+    ZhCn,
+}
+
+impl Locale {
+    pub const ALL: [Locale; 2] = [Locale::En, Locale::ZhCn];
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            Locale::En => "en",
+            Locale::ZhCn => "zh-CN",
+        }
+    }
+
+    // shown in the switcher, always in its own language
+    pub fn label(&self) -> &'static str {
+        match self {
+            Locale::En => "English",
+            Locale::ZhCn => "中文",
+        }
+    }
+
+    // "zh", "zh-CN", "zh-Hans-CN" ... all map to the chinese catalog
+    pub fn from_code(code: &str) -> Option<Self> {
+        let code = code.to_lowercase();
+        if code.starts_with("zh") {
+            Some(Locale::ZhCn)
+        } else if code.starts_with("en") {
+            Some(Locale::En)
+        } else {
+            None
+        }
+    }
+
+    fn catalog(&self) -> &'static Catalog {
+        match self {
+            Locale::En => &EN,
+            Locale::ZhCn => &ZH_CN,
+        }
+    }
+
+    // chinese has no plural forms
+    fn plural_suffix(&self, count: i64) -> &'static str {
+        match self {
+            Locale::En if count == 1 => "one",
+            _ => "other",
+        }
+    }
+}
+
+// the saved choice first, then the browser language
+fn detect() -> Locale {
+    common::get_local_storage(LOCALE_KEY)
+        .and_then(|x| Locale::from_code(&x))
+        .or_else(|| {
+            web_sys::window()
+                .and_then(|x| x.navigator().language())
+                .and_then(|x| Locale::from_code(&x))
+        })
+        .unwrap_or_default()
+}
+
+pub fn current() -> Locale {
+    CURRENT.with(|x| *x.borrow_mut().get_or_insert_with(detect))
+}
+
+pub fn set_locale(locale: Locale) {
+    CURRENT.with(|x| *x.borrow_mut() = Some(locale));
+    common::set_local_storage(LOCALE_KEY, locale.code());
+    if let Some(el) = web_sys::window()
+        .and_then(|x| x.document())
+        .and_then(|x| x.document_element())
+    {
+        el.set_attribute("lang", locale.code()).unwrap_or_default();
+    }
+}
+
+fn lookup(locale: Locale, key: &str) -> Option<&'static String> {
+    locale.catalog().get(key).or_else(|| EN.get(key))
+}
+
+// missing keys fall back to english, then to the key itself
+pub fn t(key: &str) -> String {
+    lookup(current(), key)
+        .cloned()
+        .unwrap_or_else(|| key.to_string())
+}
+
+// replaces "{name}" placeholders
+pub fn t_with(key: &str, args: &[(&str, &dyn Display)]) -> String {
+    let mut text = t(key);
+    for (name, value) in args {
+        text = text.replace(&format!("{{{name}}}"), &value.to_string());
+    }
+    text
+}
+
+// looks up "key.one" or "key.other", "{count}" is replaced
+pub fn t_plural(key: &str, count: i64) -> String {
+    let locale = current();
+    let key = format!("{key}.{}", locale.plural_suffix(count));
+    lookup(locale, &key)
+        .map(|x| x.replace("{count}", &count.to_string()))
+        .unwrap_or(key)
+}
+
+pub fn format_date<Tz: TimeZone>(time: &DateTime<Tz>) -> String
+where
+    Tz::Offset: Display,
+{
+    time.format(&t("format.date")).to_string()
+}
+
+pub fn format_datetime<Tz: TimeZone>(time: &DateTime<Tz>) -> String
+where
+    Tz::Offset: Display,
+{
+    time.format(&t("format.datetime")).to_string()
+}
+
+pub fn format_time<Tz: TimeZone>(time: &DateTime<Tz>) -> String
+where
+    Tz::Offset: Display,
+{
+    time.format(&t("format.time")).to_string()
+}
+
+// function components subscribe through use_translation and render again
+// when the locale is switched
+#[derive(Clone, PartialEq)]
+pub struct I18n {
+    pub locale: Locale,
+    set: Callback<Locale>,
+}
+
+impl I18n {
+    pub fn t(&self, key: &str) -> String {
+        t(key)
+    }
+
+    pub fn t_with(&self, key: &str, args: &[(&str, &dyn Display)]) -> String {
+        t_with(key, args)
+    }
+
+    pub fn t_plural(&self, key: &str, count: i64) -> String {
+        t_plural(key, count)
+    }
+
+    pub fn set_locale(&self, locale: Locale) {
+        self.set.emit(locale);
+    }
+}
+
+#[hook]
+pub fn use_translation() -> I18n {
+    use_context::<I18n>().expect("use_translation is called outside of I18nProvider")
+}
+
+#[derive(PartialEq, Properties)]
+pub struct I18nProviderProps {
+    #[prop_or_default]
+    pub children: Html,
+}
+
+#[function_component(I18nProvider)]
+pub fn i18n_provider(props: &I18nProviderProps) -> Html {
+    let locale = use_state(current);
+
+    use_effect_with(*locale, |locale| set_locale(*locale));
+
+    // switched in another tab
+    {
+        let locale = locale.clone();
+        use_effect_with((), move |_| {
+            let listener = sync::listen(move |change| {
+                if !change.is(LOCALE_KEY) {
+                    return;
+                }
+                if let Some(x) = change.value.and_then(|x| Locale::from_code(&x)) {
+                    set_locale(x);
+                    locale.set(x);
+                }
+            });
+            move || drop(listener)
+        });
+    }
+
+    let i18n = I18n {
+        locale: *locale,
+        set: Callback::from(move |x| {
+            set_locale(x);
+            locale.set(x);
+        }),
+    };
+
+    html! {
+        <ContextProvider<I18n> context={i18n}>
+            { props.children.clone() }
+        </ContextProvider<I18n>>
+    }
+}
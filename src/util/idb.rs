@@ -0,0 +1,106 @@
+use crate::util::common::BasicResult;
+use crate::util::error::ErrorKind;
+use futures::channel::oneshot;
+use std::cell::RefCell;
+use std::rc::Rc;
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::{JsCast, JsValue};
+use web_sys::{
+    IdbDatabase, IdbOpenDbRequest, IdbRequest, IdbTransactionMode, IdbVersionChangeEvent,
+};
+
+const DB_NAME: &str = "evolve";
+const DB_VERSION: u32 = 1;
+
+pub const CHAT_HISTORY_STORE: &str = "chat_history";
+
+const STORES: [&str; 1] = [CHAT_HISTORY_STORE];
+
+// resolve an IdbRequest into a future
+async fn wait(req: &IdbRequest) -> BasicResult<JsValue> {
+    let (tx, rx) = oneshot::channel::<Result<JsValue, JsValue>>();
+    let tx = Rc::new(RefCell::new(Some(tx)));
+    let on_success = {
+        let tx = tx.clone();
+        let req = req.clone();
+        Closure::<dyn FnMut()>::new(move || {
+            if let Some(tx) = tx.borrow_mut().take() {
+                let _ = tx.send(req.result());
+            }
+        })
+    };
+    let on_error = {
+        let tx = tx.clone();
+        let req = req.clone();
+        Closure::<dyn FnMut()>::new(move || {
+            if let Some(tx) = tx.borrow_mut().take() {
+                let err = req
+                    .error()
+                    .ok()
+                    .flatten()
+                    .map(JsValue::from)
+                    .unwrap_or(JsValue::NULL);
+                let _ = tx.send(Err(err));
+            }
+        })
+    };
+    req.set_onsuccess(Some(on_success.as_ref().unchecked_ref()));
+    req.set_onerror(Some(on_error.as_ref().unchecked_ref()));
+    let res = rx.await.map_err(|e| ErrorKind::OtherError(e.to_string()))?;
+    req.set_onsuccess(None);
+    req.set_onerror(None);
+    Ok(res?)
+}
+
+async fn open() -> BasicResult<IdbDatabase> {
+    let factory = web_sys::window()
+        .unwrap()
+        .indexed_db()?
+        .ok_or(ErrorKind::OtherError(String::from(
+            "indexed db is not supported",
+        )))?;
+    let req: IdbOpenDbRequest = factory.open_with_u32(DB_NAME, DB_VERSION)?;
+    let on_upgrade =
+        Closure::<dyn FnMut(IdbVersionChangeEvent)>::new(move |e: IdbVersionChangeEvent| {
+            let req: IdbOpenDbRequest = e.target().unwrap().unchecked_into();
+            let db: IdbDatabase = req.result().unwrap().unchecked_into();
+            for store in STORES {
+                if !db.object_store_names().contains(store) {
+                    db.create_object_store(store).unwrap();
+                }
+            }
+        });
+    req.set_onupgradeneeded(Some(on_upgrade.as_ref().unchecked_ref()));
+    let db = wait(&req).await?;
+    req.set_onupgradeneeded(None);
+    Ok(db.unchecked_into())
+}
+
+pub async fn get(store: &str, key: &str) -> BasicResult<Option<String>> {
+    let db = open().await?;
+    let tx = db.transaction_with_str(store)?;
+    let req = tx.object_store(store)?.get(&JsValue::from_str(key))?;
+    let res = wait(&req).await;
+    db.close();
+    Ok(res?.as_string())
+}
+
+pub async fn put(store: &str, key: &str, value: &str) -> BasicResult<()> {
+    let db = open().await?;
+    let tx = db.transaction_with_str_and_mode(store, IdbTransactionMode::Readwrite)?;
+    let req = tx
+        .object_store(store)?
+        .put_with_key(&JsValue::from_str(value), &JsValue::from_str(key))?;
+    let res = wait(&req).await;
+    db.close();
+    res.map(|_| ())
+}
+
+pub async fn delete(store: &str, key: &str) -> BasicResult<()> {
+    let db = open().await?;
+    let tx = db.transaction_with_str_and_mode(store, IdbTransactionMode::Readwrite)?;
+    let req = tx.object_store(store)?.delete(&JsValue::from_str(key))?;
+    let res = wait(&req).await;
+    db.close();
+    res.map(|_| ())
+}
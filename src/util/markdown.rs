@@ -0,0 +1,91 @@
+use crate::util::sanitize::{self, is_safe_url};
+use pulldown_cmark::{html, CowStr, Event, Options, Parser, Tag};
+use yew::virtual_dom::VNode;
+
+fn strip_unsafe_url(url: CowStr) -> CowStr {
+    if is_safe_url(&url) {
+        url
+    } else {
+        CowStr::from("")
+    }
+}
+
+// byte ranges of "@name" in text, case insensitive and not followed by a word character
+pub fn find_mentions(text: &str, name: &str) -> Vec<(usize, usize)> {
+    let mut res = vec![];
+    if name.is_empty() {
+        return res;
+    }
+    for (start, _) in text.match_indices('@') {
+        if res.last().is_some_and(|&(_, end)| start < end) {
+            continue;
+        }
+        let rest = &text[start + 1..];
+        let mut end = start + 1;
+        let mut chars = rest.chars();
+        let matched = name.chars().all(|n| match chars.next() {
+            Some(c) if c.to_lowercase().eq(n.to_lowercase()) => {
+                end += c.len_utf8();
+                true
+            }
+            _ => false,
+        });
+        let boundary = text[end..]
+            .chars()
+            .next()
+            .map(|c| !c.is_alphanumeric() && c != '_')
+            .unwrap_or(true);
+        if matched && boundary {
+            res.push((start, end));
+        }
+    }
+    res
+}
+
+fn highlight_mentions<'a>(text: CowStr<'a>, mention: Option<&str>) -> Vec<Event<'a>> {
+    let ranges = mention.map(|x| find_mentions(&text, x)).unwrap_or_default();
+    if ranges.is_empty() {
+        return vec![Event::Text(text)];
+    }
+    let mut res = vec![];
+    let mut last = 0;
+    for (start, end) in ranges {
+        res.push(Event::Text(CowStr::from(text[last..start].to_string())));
+        res.push(Event::Html(CowStr::from("<mark class=\"mention\">")));
+        res.push(Event::Text(CowStr::from(text[start..end].to_string())));
+        res.push(Event::Html(CowStr::from("</mark>")));
+        last = end;
+    }
+    res.push(Event::Text(CowStr::from(text[last..].to_string())));
+    res
+}
+
+// raw html in the source is rendered as text, and only http(s)/mailto/relative urls are kept
+pub fn to_html(text: &str, mention: Option<&str>) -> String {
+    let options = Options::ENABLE_STRIKETHROUGH | Options::ENABLE_TABLES;
+    let parser = Parser::new_ext(text, options)
+        .map(|event| match event {
+            Event::Html(raw) => Event::Text(raw),
+            event => event,
+        })
+        .flat_map(|event| match event {
+            Event::Text(text) => highlight_mentions(text, mention),
+            event => vec![event],
+        });
+    let parser = parser.map(|event| match event {
+        Event::Start(Tag::Link(link_type, url, title)) => {
+            Event::Start(Tag::Link(link_type, strip_unsafe_url(url), title))
+        }
+        Event::Start(Tag::Image(link_type, url, title)) => {
+            Event::Start(Tag::Image(link_type, strip_unsafe_url(url), title))
+        }
+        event => event,
+    });
+    let mut res = String::new();
+    html::push_html(&mut res, parser);
+    res
+}
+
+pub fn render(text: &str, mention: Option<&str>) -> VNode {
+    sanitize::render("div", &to_html(text, mention))
+}
@@ -2,6 +2,22 @@
 
 pub const TOKEN_KEY: &str = "evolve_token";
 
+pub mod challenge;
 pub mod common;
+pub mod credential;
 pub mod error;
+pub mod form;
+pub mod highlight;
+pub mod i18n;
+pub mod idb;
+pub mod markdown;
+pub mod password;
+pub mod qr;
 pub mod request;
+pub mod sanitize;
+pub mod session;
+pub mod sso;
+pub mod sync;
+pub mod theme;
+pub mod time;
+pub mod verify_code;
@@ -0,0 +1,292 @@
+use crate::util::common::BasicResult;
+use crate::util::error::{ErrorKind, ToError};
+use crate::util::form::FormHandle;
+use crate::util::i18n::{self, use_translation};
+use crate::util::request::{self, Host};
+use serde::Deserialize;
+use std::cell::RefCell;
+use wasm_bindgen_futures::spawn_local;
+use yew::prelude::*;
+
+const SYMBOLS: &str = "!\"#$%&'()*+,-./:;<=>?@[\\]^_`{|}~ ";
+
+// the rules of the server, the defaults are used until they are loaded
+#[derive(Deserialize, Clone, PartialEq, Debug)]
+#[serde(default)]
+pub struct PasswordPolicy {
+    pub min_len: usize,
+    pub max_len: usize,
+    pub require_lower: bool,
+    pub require_upper: bool,
+    pub require_digit: bool,
+    pub require_symbol: bool,
+    // compared case insensitive, e.g. "password", "12345678"
+    pub banned: Vec<String>,
+}
+
+impl Default for PasswordPolicy {
+    fn default() -> Self {
+        Self {
+            min_len: 6,
+            max_len: 64,
+            require_lower: true,
+            require_upper: false,
+            require_digit: true,
+            require_symbol: false,
+            banned: vec![],
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Rule {
+    Length,
+    Lower,
+    Upper,
+    Digit,
+    Symbol,
+    NotBanned,
+}
+
+impl Rule {
+    pub fn label(&self, policy: &PasswordPolicy) -> String {
+        match self {
+            Rule::Length => i18n::t_with(
+                "password.rule.length",
+                &[("min", &policy.min_len), ("max", &policy.max_len)],
+            ),
+            Rule::Lower => i18n::t("password.rule.lower"),
+            Rule::Upper => i18n::t("password.rule.upper"),
+            Rule::Digit => i18n::t("password.rule.digit"),
+            Rule::Symbol => i18n::t("password.rule.symbol"),
+            Rule::NotBanned => i18n::t("password.rule.not_banned"),
+        }
+    }
+}
+
+fn is_symbol(c: char) -> bool {
+    SYMBOLS.contains(c)
+}
+
+impl PasswordPolicy {
+    // the rules this policy demands, in the order they are shown
+    pub fn rules(&self) -> Vec<Rule> {
+        [
+            (Rule::Length, true),
+            (Rule::Lower, self.require_lower),
+            (Rule::Upper, self.require_upper),
+            (Rule::Digit, self.require_digit),
+            (Rule::Symbol, self.require_symbol),
+            (Rule::NotBanned, !self.banned.is_empty()),
+        ]
+        .into_iter()
+        .filter(|x| x.1)
+        .map(|x| x.0)
+        .collect()
+    }
+
+    pub fn passes(&self, rule: Rule, pwd: &str) -> bool {
+        match rule {
+            Rule::Length => (self.min_len..=self.max_len).contains(&pwd.chars().count()),
+            Rule::Lower => pwd.chars().any(|x| x.is_lowercase()),
+            Rule::Upper => pwd.chars().any(|x| x.is_uppercase()),
+            Rule::Digit => pwd.chars().any(|x| x.is_ascii_digit()),
+            Rule::Symbol => pwd.chars().any(is_symbol),
+            Rule::NotBanned => !self.banned.iter().any(|x| x.eq_ignore_ascii_case(pwd)),
+        }
+    }
+
+    pub fn validate(&self, pwd: &str) -> BasicResult<()> {
+        if pwd.is_empty() {
+            return Err(i18n::t("validate.pwd.empty").to_validation_error());
+        }
+        match self.rules().into_iter().find(|x| !self.passes(*x, pwd)) {
+            Some(Rule::NotBanned) => Err(i18n::t("validate.pwd.banned").to_validation_error()),
+            Some(rule) => Err(
+                i18n::t_with("validate.pwd.rule", &[("rule", &rule.label(self))])
+                    .to_validation_error(),
+            ),
+            None => Ok(()),
+        }
+    }
+}
+
+thread_local! {
+    static POLICY: RefCell<Option<PasswordPolicy>> = RefCell::new(None);
+}
+
+pub fn policy() -> PasswordPolicy {
+    POLICY.with(|x| x.borrow().clone().unwrap_or_default())
+}
+
+// fetched once per page load, a server without a policy keeps the defaults
+pub async fn load() -> PasswordPolicy {
+    if POLICY.with(|x| x.borrow().is_some()) {
+        return policy();
+    }
+    match request::get::<PasswordPolicy, Vec<(&str, &str)>, _>(
+        Host::AxumBase,
+        "/auth/password_policy",
+        None,
+    )
+    .await
+    {
+        Ok(res) => POLICY.with(|x| *x.borrow_mut() = Some(res.data.unwrap_or_default())),
+        Err(ErrorKind::Status(404, _)) => {
+            POLICY.with(|x| *x.borrow_mut() = Some(PasswordPolicy::default()))
+        }
+        Err(err) => log::warn!("load password policy error: {}", err),
+    }
+    policy()
+}
+
+#[hook]
+pub fn use_password_policy() -> PasswordPolicy {
+    let policy = use_state(policy);
+    {
+        let policy = policy.clone();
+        use_effect_with((), move |_| {
+            spawn_local(async move {
+                let res = load().await;
+                if *policy != res {
+                    policy.set(res);
+                }
+            });
+        });
+    }
+    (*policy).clone()
+}
+
+// the validators of form::pwd read the policy when they run, the fields are
+// checked again once the policy of the server arrived
+#[hook]
+pub fn use_policy_revalidation(form: &FormHandle, names: &'static [&'static str]) {
+    let policy = use_password_policy();
+    let form = form.clone();
+    use_effect_with(policy, move |_| form.revalidate(names));
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Strength {
+    VeryWeak,
+    Weak,
+    Fair,
+    Strong,
+    VeryStrong,
+}
+
+impl Strength {
+    pub fn from_bits(bits: f64) -> Self {
+        match bits {
+            x if x < 28.0 => Strength::VeryWeak,
+            x if x < 36.0 => Strength::Weak,
+            x if x < 60.0 => Strength::Fair,
+            x if x < 128.0 => Strength::Strong,
+            _ => Strength::VeryStrong,
+        }
+    }
+
+    pub fn level(&self) -> u32 {
+        *self as u32
+    }
+
+    pub fn color(&self) -> &'static str {
+        match self {
+            Strength::VeryWeak | Strength::Weak => "is-danger",
+            Strength::Fair => "is-warning",
+            Strength::Strong | Strength::VeryStrong => "is-success",
+        }
+    }
+
+    pub fn label_key(&self) -> &'static str {
+        match self {
+            Strength::VeryWeak => "password.strength.very_weak",
+            Strength::Weak => "password.strength.weak",
+            Strength::Fair => "password.strength.fair",
+            Strength::Strong => "password.strength.strong",
+            Strength::VeryStrong => "password.strength.very_strong",
+        }
+    }
+}
+
+// bits of a random password of the same length and character classes,
+// repeated and sequential characters, e.g. "aaa" or "123", count half
+pub fn entropy(pwd: &str) -> f64 {
+    let chars = pwd.chars().collect::<Vec<char>>();
+    let mut pool = 0;
+    if chars.iter().any(|x| x.is_ascii_lowercase()) {
+        pool += 26;
+    }
+    if chars.iter().any(|x| x.is_ascii_uppercase()) {
+        pool += 26;
+    }
+    if chars.iter().any(|x| x.is_ascii_digit()) {
+        pool += 10;
+    }
+    if chars.iter().any(|x| is_symbol(*x)) {
+        pool += SYMBOLS.len();
+    }
+    if chars.iter().any(|x| !x.is_ascii()) {
+        pool += 100;
+    }
+    if pool == 0 {
+        return 0.0;
+    }
+    let len = chars
+        .iter()
+        .enumerate()
+        .map(|(i, x)| match i {
+            0 => 1.0,
+            _ if (*x as i64 - chars[i - 1] as i64).abs() <= 1 => 0.5,
+            _ => 1.0,
+        })
+        .sum::<f64>();
+    len * (pool as f64).log2()
+}
+
+pub fn strength(pwd: &str, policy: &PasswordPolicy) -> Strength {
+    if !policy.passes(Rule::NotBanned, pwd) {
+        return Strength::VeryWeak;
+    }
+    Strength::from_bits(entropy(pwd))
+}
+
+#[derive(PartialEq, Properties)]
+pub struct PasswordStrengthProps {
+    pub value: String,
+}
+
+// a meter and the rules of the policy, shown under a new password
+#[function_component(PasswordStrength)]
+pub fn password_strength(props: &PasswordStrengthProps) -> Html {
+    let i18n = use_translation();
+    let policy = use_password_policy();
+    let strength = strength(&props.value, &policy);
+    let empty = props.value.is_empty();
+    let level = if empty { 0 } else { strength.level() + 1 };
+
+    html! {
+        <div class="password-strength">
+            <progress class={classes!("progress", "is-small", "mb-1", (!empty).then_some(strength.color()))}
+                value={level.to_string()} max="5"/>
+            if !empty {
+                <p class="help">{i18n.t("password.strength")}{i18n.t(strength.label_key())}</p>
+            }
+            <ul class="password-rules">
+            {
+                policy.rules().into_iter().map(|rule| {
+                    let passed = policy.passes(rule, &props.value);
+                    html! {
+                        <li class={classes!(passed.then_some("has-text-success"))}>
+                            <span class="icon is-small">
+                                <i class={if passed { "fa-solid fa-check" } else { "fa-solid fa-circle fa-2xs" }}></i>
+                            </span>
+                            <span>{rule.label(&policy)}</span>
+                        </li>
+                    }
+                }).collect::<Html>()
+            }
+            </ul>
+        </div>
+    }
+}
@@ -0,0 +1,32 @@
+use qrcode::{Color, QrCode};
+use yew::prelude::*;
+
+// modules of white space around the code, readers need at least 4
+const QUIET_ZONE: usize = 4;
+
+// an svg with one path for all dark modules, e.g. render("otpauth://...", 200)
+pub fn render(value: &str, size: u32) -> Html {
+    let code = match QrCode::new(value.as_bytes()) {
+        Ok(v) => v,
+        Err(err) => {
+            log::error!("qr code error: {}", err);
+            return html! {};
+        }
+    };
+    let width = code.width();
+    let mut path = String::new();
+    for (i, color) in code.to_colors().into_iter().enumerate() {
+        if color == Color::Dark {
+            let (x, y) = (i % width + QUIET_ZONE, i / width + QUIET_ZONE);
+            path.push_str(&format!("M{x},{y}h1v1h-1z"));
+        }
+    }
+    let view_box = format!("0 0 {0} {0}", width + QUIET_ZONE * 2);
+    html! {
+        <svg class="qr-code" width={size.to_string()} height={size.to_string()} viewBox={view_box}
+            shape-rendering="crispEdges" xmlns="http://www.w3.org/2000/svg">
+            <rect width="100%" height="100%" fill="#ffffff"/>
+            <path d={path} fill="#000000"/>
+        </svg>
+    }
+}
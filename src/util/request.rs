@@ -1,15 +1,25 @@
 use crate::util::common;
-use crate::util::error::ToError;
+use crate::util::error::{ErrorKind, ToError};
+use crate::util::i18n;
 use common::BasicResult;
+use futures::channel::oneshot;
 use gloo_net::http::{Method, RequestBuilder};
 use gloo_net::websocket::futures::WebSocket;
 use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::rc::Rc;
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::JsCast;
 use wasm_bindgen_futures::spawn_local;
+use web_sys::{File, FormData, ProgressEvent, XmlHttpRequest};
+use yew::Callback;
 
 #[derive(Deserialize)]
 pub struct ResultData<T> {
     pub data: Option<T>,
+    // the evolve_axum server calls it message
+    #[serde(alias = "message")]
     pub msg: Option<String>,
     pub total: Option<usize>,
 }
@@ -18,6 +28,16 @@ pub struct ResultData<T> {
 pub enum Host {
     ApiBase,
     Base,
+    // the evolve_axum server of the generated client, it issues the tokens
+    AxumBase,
+}
+
+impl Host {
+    // 401 and 404 go to the error pages, those of the evolve_axum server are
+    // handed to the caller like the errors of the generated client
+    fn redirects(&self) -> bool {
+        !matches!(self, Host::AxumBase)
+    }
 }
 
 impl std::fmt::Display for Host {
@@ -25,6 +45,7 @@ impl std::fmt::Display for Host {
         match self {
             Host::ApiBase => f.write_str("http://localhost:8881/api"),
             Host::Base => f.write_str("http://localhost:8881"),
+            Host::AxumBase => f.write_str(common::BASE_URL),
         }
     }
 }
@@ -43,13 +64,22 @@ fn build_request(method: Method, host: Host, path: &str) -> RequestBuilder {
     build_header(req)
 }
 
-async fn send<Res>(req: gloo_net::http::Request) -> BasicResult<ResultData<Res>>
+async fn send<Res>(req: gloo_net::http::Request, redirects: bool) -> BasicResult<ResultData<Res>>
 where
     Res: DeserializeOwned,
 {
     let response = req.send().await?;
     let status = response.status();
     // let url = response.url();
+    if (status == 401 || status == 404) && !redirects {
+        // e.g. a route the server does not have answers without a body
+        let msg = response
+            .json::<ResultData<serde_json::Value>>()
+            .await
+            .ok()
+            .and_then(|x| x.msg);
+        return Err(status_error(status, msg));
+    }
     if status == 401 || status == 404 {
         common::redirect(&format!("/{status}"));
 
@@ -59,20 +89,30 @@ where
             total: None,
         });
     }
-    let status_first = status / 100;
     let result: ResultData<Res> = response.json().await.map_err(|e| {
         log::error!("json umarshal error: {}", e);
         e
     })?;
-    match status_first {
-        4 | 5 => match status {
-            452 => Err(result.msg.unwrap().to_hint()),
-            _ => Err(result.msg.unwrap().to_server_error()),
-        },
+    check_status(status, result)
+}
+
+fn check_status<Res>(status: u16, result: ResultData<Res>) -> BasicResult<ResultData<Res>> {
+    match status / 100 {
+        4 | 5 => Err(status_error(status, result.msg)),
         _ => Ok(result),
     }
 }
 
+// an error body without msg gets a generic one
+fn status_error(status: u16, msg: Option<String>) -> ErrorKind {
+    let msg = msg.unwrap_or_else(|| i18n::t_with("error.status", &[("status", &status)]));
+    match status {
+        401 | 404 => ErrorKind::Status(status, msg),
+        452 => msg.to_hint(),
+        _ => msg.to_server_error(),
+    }
+}
+
 pub fn open_ws() -> BasicResult<WebSocket> {
     let token = common::get_token().unwrap();
     spawn_local(async move {
@@ -102,12 +142,13 @@ where
     Res: DeserializeOwned,
     V: AsRef<str>,
 {
+    let redirects = host.redirects();
     let req = match params {
         Some(p) => build_request(Method::GET, host, path).query(p),
         None => build_request(Method::GET, host, path),
     };
 
-    send(req.build()?).await
+    send(req.build()?, redirects).await
 }
 
 #[allow(unused)]
@@ -116,9 +157,10 @@ where
     Body: Serialize,
     Res: DeserializeOwned,
 {
+    let redirects = host.redirects();
     let body = serde_json::to_string(body)?;
     let req = build_request(Method::PUT, host, path).body(body)?;
-    send(req).await
+    send(req, redirects).await
 }
 
 #[allow(unused)]
@@ -131,9 +173,10 @@ where
     Body: Serialize,
     Res: DeserializeOwned,
 {
+    let redirects = host.redirects();
     let body = serde_json::to_string(body)?;
     let req = build_request(Method::POST, host, path).body(body)?;
-    send(req).await
+    send(req, redirects).await
 }
 
 #[allow(unused)]
@@ -146,7 +189,103 @@ where
     Body: Serialize,
     Res: DeserializeOwned,
 {
+    let redirects = host.redirects();
     let body = serde_json::to_string(body)?;
     let req = build_request(Method::DELETE, host, path).body(body)?;
-    send(req).await
+    send(req, redirects).await
+}
+
+// for endpoints answering without the ResultData envelope, e.g. the token of
+// /auth/authorize
+pub async fn post_plain<Res, Body>(host: Host, path: &str, body: &Body) -> BasicResult<Res>
+where
+    Body: Serialize,
+    Res: DeserializeOwned,
+{
+    let redirects = host.redirects();
+    let body = serde_json::to_string(body)?;
+    let response = build_request(Method::POST, host, path)
+        .body(body)?
+        .send()
+        .await?;
+    let status = response.status();
+    if status / 100 == 4 || status / 100 == 5 {
+        if redirects && (status == 401 || status == 404) {
+            common::redirect(&format!("/{status}"));
+        }
+        let msg = response
+            .json::<ResultData<serde_json::Value>>()
+            .await
+            .ok()
+            .and_then(|x| x.msg);
+        return Err(status_error(status, msg));
+    }
+    Ok(response.json::<Res>().await?)
+}
+
+// multipart upload of a single file, fetch has no upload progress so XMLHttpRequest is used
+pub async fn upload<Res>(
+    host: Host,
+    path: &str,
+    file: &File,
+    onprogress: Callback<u32>,
+) -> BasicResult<ResultData<Res>>
+where
+    Res: DeserializeOwned,
+{
+    let redirects = host.redirects();
+    let xhr = XmlHttpRequest::new()?;
+    xhr.open("POST", &format!("{}{}", host, path))?;
+    if let Ok(v) = common::get_token() {
+        xhr.set_request_header(crate::util::TOKEN_KEY, format!("Bearer {}", v).as_str())?;
+    }
+    let form = FormData::new()?;
+    form.append_with_blob_and_filename("file", file, &file.name())?;
+
+    let on_progress = Closure::<dyn FnMut(ProgressEvent)>::new(move |e: ProgressEvent| {
+        if e.length_computable() && e.total() > 0.0 {
+            onprogress.emit((e.loaded() * 100.0 / e.total()) as u32);
+        }
+    });
+    xhr.upload()?
+        .set_onprogress(Some(on_progress.as_ref().unchecked_ref()));
+
+    let (tx, rx) = oneshot::channel::<()>();
+    let tx = Rc::new(RefCell::new(Some(tx)));
+    let on_loadend = Closure::<dyn FnMut()>::new(move || {
+        if let Some(tx) = tx.borrow_mut().take() {
+            let _ = tx.send(());
+        }
+    });
+    xhr.set_onloadend(Some(on_loadend.as_ref().unchecked_ref()));
+    xhr.send_with_opt_form_data(Some(&form))?;
+    rx.await.map_err(|e| ErrorKind::OtherError(e.to_string()))?;
+
+    let status = xhr.status()?;
+    if status == 0 {
+        return Err(ErrorKind::OtherError(String::from(
+            "upload was interrupted",
+        )));
+    }
+    let text = xhr.response_text()?.unwrap_or_default();
+    if (status == 401 || status == 404) && !redirects {
+        let msg = serde_json::from_str::<ResultData<serde_json::Value>>(&text)
+            .ok()
+            .and_then(|x| x.msg);
+        return Err(status_error(status, msg));
+    }
+    if status == 401 || status == 404 {
+        common::redirect(&format!("/{status}"));
+
+        return Ok(ResultData {
+            data: None,
+            msg: None,
+            total: None,
+        });
+    }
+    let result: ResultData<Res> = serde_json::from_str(&text).map_err(|e| {
+        log::error!("json umarshal error: {}", e);
+        e
+    })?;
+    check_status(status, result)
 }
@@ -0,0 +1,157 @@
+use wasm_bindgen::JsCast;
+use web_sys::{DomParser, Element, Node, SupportedType};
+use yew::virtual_dom::{VNode, VTag, VText};
+
+// tags that may be rendered and the attributes kept on them,
+// other tags are unwrapped, i.e. only their content is rendered
+const ALLOWED_TAGS: [(&str, &[&str]); 33] = [
+    ("a", &["href", "title"]),
+    ("b", &[]),
+    ("blockquote", &[]),
+    ("br", &[]),
+    ("code", &[]),
+    ("del", &[]),
+    ("div", &[]),
+    ("em", &[]),
+    ("h1", &[]),
+    ("h2", &[]),
+    ("h3", &[]),
+    ("h4", &[]),
+    ("h5", &[]),
+    ("h6", &[]),
+    ("hr", &[]),
+    ("i", &[]),
+    ("img", &["src", "alt", "title"]),
+    ("li", &[]),
+    ("mark", &["class"]),
+    ("ol", &["start"]),
+    ("p", &[]),
+    ("pre", &[]),
+    ("s", &[]),
+    ("span", &["class"]),
+    ("strong", &[]),
+    ("table", &[]),
+    ("tbody", &[]),
+    ("td", &["align"]),
+    ("th", &["align"]),
+    ("thead", &[]),
+    ("tr", &[]),
+    ("u", &[]),
+    ("ul", &[]),
+];
+// dropped together with their content
+const DROPPED_TAGS: [&str; 9] = [
+    "script", "style", "iframe", "object", "embed", "template", "noscript", "textarea", "title",
+];
+const VOID_TAGS: [&str; 3] = ["br", "hr", "img"];
+const URL_ATTRIBUTES: [&str; 2] = ["href", "src"];
+// search hits from the server and chat mentions
+const ALLOWED_CLASSES: [&str; 2] = ["highlight", "mention"];
+
+pub fn is_safe_url(url: &str) -> bool {
+    // browsers drop tabs and newlines anywhere in a url
+    let url = url
+        .trim()
+        .chars()
+        .filter(|x| !matches!(x, '\t' | '\n' | '\r'))
+        .collect::<String>()
+        .to_lowercase();
+    // "//host" and "/\host" are protocol-relative, i.e. off-site
+    if url.starts_with("//") || url.starts_with("/\\") {
+        return false;
+    }
+    ["http://", "https://", "mailto:", "/", "#"]
+        .iter()
+        .any(|x| url.starts_with(x))
+}
+
+fn sanitize_attribute(name: &str, value: String) -> Option<String> {
+    if URL_ATTRIBUTES.contains(&name) {
+        return is_safe_url(&value).then_some(value);
+    }
+    if name == "class" {
+        let class = value
+            .split_whitespace()
+            .filter(|x| ALLOWED_CLASSES.contains(x))
+            .collect::<Vec<&str>>()
+            .join(" ");
+        return (!class.is_empty()).then_some(class);
+    }
+    Some(value)
+}
+
+fn convert_children(node: &Node, res: &mut Vec<VNode>) {
+    let children = node.child_nodes();
+    for i in 0..children.length() {
+        if let Some(child) = children.item(i) {
+            convert(&child, res);
+        }
+    }
+}
+
+fn convert(node: &Node, res: &mut Vec<VNode>) {
+    match node.node_type() {
+        Node::TEXT_NODE => {
+            let text = node.text_content().unwrap_or_default();
+            res.push(VText::new(text).into());
+        }
+        Node::ELEMENT_NODE => {
+            let el: &Element = match node.dyn_ref() {
+                Some(v) => v,
+                None => return,
+            };
+            let name = el.local_name();
+            if DROPPED_TAGS.contains(&name.as_str()) {
+                return;
+            }
+            let attributes = match ALLOWED_TAGS.iter().find(|(tag, _)| *tag == name) {
+                Some((_, attributes)) => attributes,
+                None => return convert_children(node, res),
+            };
+            let mut tag = VTag::new(name.clone());
+            for &attribute in attributes.iter() {
+                if let Some(value) = el
+                    .get_attribute(attribute)
+                    .and_then(|x| sanitize_attribute(attribute, x))
+                {
+                    tag.add_attribute(attribute, value);
+                }
+            }
+            if name == "a" {
+                tag.add_attribute("rel", "noopener noreferrer");
+            }
+            if !VOID_TAGS.contains(&name.as_str()) {
+                let mut children = vec![];
+                convert_children(node, &mut children);
+                tag.add_children(children);
+            }
+            res.push(tag.into());
+        }
+        // comments, processing instructions ...
+        _ => {}
+    }
+}
+
+// parsed by the browser into an inert document, scripts are not run and
+// nothing is loaded, then only allow-listed tags and attributes are copied
+pub fn to_vnodes(html: &str) -> Vec<VNode> {
+    let body = DomParser::new()
+        .and_then(|x| x.parse_from_string(html, SupportedType::TextHtml))
+        .ok()
+        .and_then(|x| x.body());
+    match body {
+        Some(body) => {
+            let mut res = vec![];
+            convert_children(&body, &mut res);
+            res
+        }
+        None => vec![VText::new(html.to_string()).into()],
+    }
+}
+
+// replaces setting inner_html, e.g. render("td", "<b>bob</b>")
+pub fn render(tag: &str, html: &str) -> VNode {
+    let mut res = VTag::new(tag.to_string());
+    res.add_children(to_vnodes(html));
+    res.into()
+}
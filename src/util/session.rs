@@ -0,0 +1,122 @@
+use crate::util::common::{self, BasicResult, CurrentUser, CurrentUserHandle};
+use crate::util::credential;
+use crate::util::error::ErrorKind;
+use crate::util::i18n;
+use crate::util::request::{self, Host};
+use crate::util::sync;
+use serde::Deserialize;
+use yew::prelude::*;
+
+// a signed in device, every token the server issued and did not revoke yet
+#[derive(Deserialize, Clone, PartialEq, Debug)]
+pub struct Session {
+    pub id: String,
+    #[serde(default)]
+    pub ip: Option<String>,
+    #[serde(default)]
+    pub user_agent: Option<String>,
+    pub created_at: i64,
+    pub last_seen: i64,
+    // the session of the token sending the request
+    #[serde(default)]
+    pub current: bool,
+}
+
+pub async fn list() -> BasicResult<Vec<Session>> {
+    let res =
+        request::get::<Vec<Session>, Vec<(&str, &str)>, _>(Host::AxumBase, "/user/sessions", None)
+            .await?;
+    Ok(res.data.unwrap_or_default())
+}
+
+pub async fn revoke(id: &str) -> BasicResult<()> {
+    request::delete::<(), _>(Host::AxumBase, &format!("/user/sessions/{}", id), &()).await?;
+    Ok(())
+}
+
+// every session including the current one
+pub async fn revoke_all() -> BasicResult<()> {
+    request::delete::<(), _>(Host::AxumBase, "/user/sessions", &()).await?;
+    Ok(())
+}
+
+// only local state is left once the token is revoked or the server is unreachable
+pub fn clear_local() {
+    common::delete_current_user().unwrap_or_else(|x| {
+        log::error!("{:?}", x);
+    });
+    credential::prevent_silent_access();
+}
+
+// the token is revoked first so it can not be used again, signing out never
+// fails. a 401 means the token expired or was revoked already
+pub async fn logout() {
+    if common::get_token().is_ok() {
+        match request::post::<(), _>(Host::AxumBase, "/auth/logout", &()).await {
+            Ok(_) | Err(ErrorKind::Status(401, _)) => {}
+            Err(err) => log::warn!("revoke token error: {}", err),
+        }
+    }
+    clear_local();
+}
+
+// keeps the tab in step with the others of the browser, current_user is the
+// handle of a signed in page and None on the login page. a refreshed token
+// needs nothing, requests read it from localStorage every time
+#[hook]
+pub fn use_session_sync(current_user: Option<CurrentUserHandle>) {
+    use_effect_with((), move |_| {
+        let listener = sync::listen(move |change| {
+            if change.is(crate::util::TOKEN_KEY) {
+                match (&current_user, &change.value) {
+                    // signed out in another tab, the storage is already cleared
+                    (Some(_), None) => return common::redirect("/login"),
+                    // signed in in another tab
+                    (None, Some(_)) => return common::redirect("/main/user"),
+                    _ => {}
+                }
+            }
+            if let Some(handle) = current_user.as_ref().filter(|_| change.is("current_user")) {
+                handle.set(
+                    change
+                        .value
+                        .and_then(|x| serde_json::from_str::<CurrentUser>(&x).ok()),
+                );
+            }
+        });
+        move || drop(listener)
+    });
+}
+
+// e.g. "Chrome on Windows", the raw user agent is shown as tooltip
+pub fn describe_agent(user_agent: &str) -> String {
+    // the order matters, edge and opera also claim to be chrome, chrome claims to be safari
+    let browser = [
+        ("Edg/", "Edge"),
+        ("OPR/", "Opera"),
+        ("Firefox/", "Firefox"),
+        ("Chrome/", "Chrome"),
+        ("Safari/", "Safari"),
+    ]
+    .into_iter()
+    .find(|x| user_agent.contains(x.0))
+    .map(|x| x.1);
+    let os = [
+        ("Android", "Android"),
+        ("iPhone", "iOS"),
+        ("iPad", "iPadOS"),
+        ("Windows", "Windows"),
+        ("Mac OS X", "macOS"),
+        ("Linux", "Linux"),
+    ]
+    .into_iter()
+    .find(|x| user_agent.contains(x.0))
+    .map(|x| x.1);
+    match (browser, os) {
+        (Some(browser), Some(os)) => {
+            i18n::t_with("sessions.agent", &[("browser", &browser), ("os", &os)])
+        }
+        (Some(v), None) | (None, Some(v)) => v.to_string(),
+        (None, None) => user_agent.to_string(),
+    }
+}
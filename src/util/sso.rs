@@ -0,0 +1,186 @@
+use crate::util::common::BasicResult;
+use crate::util::error::ErrorKind;
+use crate::util::i18n;
+use crate::util::request::{self, Host};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+pub const CALLBACK_PATH: &str = "/sso/callback";
+// sessionStorage, the flow starts and ends in the same tab
+const PENDING_KEY: &str = "sso_pending";
+
+// the identity provider, None when sso is not configured
+#[derive(Deserialize, Clone, PartialEq, Debug)]
+pub struct SsoConfig {
+    // shown on the button, e.g. "Okta"
+    pub name: String,
+    pub authorize_url: String,
+    pub client_id: String,
+    #[serde(default = "default_scope")]
+    pub scope: String,
+}
+
+fn default_scope() -> String {
+    String::from("openid email profile")
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub enum Intent {
+    SignIn,
+    // adds the identity to the signed in user
+    Link,
+}
+
+// kept between leaving for the provider and coming back
+#[derive(Serialize, Deserialize)]
+struct Pending {
+    intent: Intent,
+    state: String,
+    nonce: String,
+    verifier: String,
+}
+
+// sent to the server, it exchanges the code with the client secret and checks
+// the nonce of the id token
+#[derive(Serialize)]
+pub struct CodeReq {
+    pub code: String,
+    pub code_verifier: String,
+    pub redirect_uri: String,
+    pub nonce: String,
+}
+
+#[derive(Deserialize)]
+pub struct SsoLoginRes {
+    pub access_token: String,
+    #[serde(default)]
+    pub totp_required: bool,
+}
+
+// an identity of the provider linked to the signed in user
+#[derive(Deserialize, Clone, PartialEq, Debug)]
+pub struct Identity {
+    pub id: String,
+    pub provider: String,
+    #[serde(default)]
+    pub email: Option<String>,
+    pub created_at: i64,
+}
+
+// a server without the route does not offer sso either
+pub async fn config() -> Option<SsoConfig> {
+    match request::get::<SsoConfig, Vec<(&str, &str)>, _>(Host::AxumBase, "/auth/sso/config", None)
+        .await
+    {
+        Ok(res) => res.data,
+        Err(ErrorKind::Status(404, _)) => None,
+        Err(err) => {
+            log::warn!("load sso config error: {}", err);
+            None
+        }
+    }
+}
+
+fn random(len: usize) -> BasicResult<String> {
+    let mut buf = vec![0u8; len];
+    getrandom::getrandom(&mut buf).map_err(|e| ErrorKind::OtherError(e.to_string()))?;
+    Ok(URL_SAFE_NO_PAD.encode(buf))
+}
+
+// S256 of rfc 7636, the verifier never leaves the browser before the exchange
+fn challenge(verifier: &str) -> String {
+    URL_SAFE_NO_PAD.encode(Sha256::digest(verifier.as_bytes()))
+}
+
+fn redirect_uri() -> String {
+    let origin = web_sys::window()
+        .and_then(|x| x.location().origin().ok())
+        .unwrap_or_default();
+    format!("{}{}", origin, CALLBACK_PATH)
+}
+
+fn session_storage() -> Option<web_sys::Storage> {
+    web_sys::window()?.session_storage().ok().flatten()
+}
+
+fn encode(value: &str) -> String {
+    String::from(js_sys::encode_uri_component(value))
+}
+
+// leaves the app for the login page of the provider
+pub fn start(config: &SsoConfig, intent: Intent) -> BasicResult<()> {
+    let pending = Pending {
+        intent,
+        state: random(32)?,
+        nonce: random(32)?,
+        // 43 characters, the minimum of rfc 7636
+        verifier: random(32)?,
+    };
+    let url = format!(
+        "{}{}response_type=code&client_id={}&redirect_uri={}&scope={}&state={}&nonce={}&code_challenge={}&code_challenge_method=S256",
+        config.authorize_url,
+        if config.authorize_url.contains('?') { '&' } else { '?' },
+        encode(&config.client_id),
+        encode(&redirect_uri()),
+        encode(&config.scope),
+        pending.state,
+        pending.nonce,
+        challenge(&pending.verifier),
+    );
+    let storage = session_storage()
+        .ok_or_else(|| ErrorKind::OtherError(String::from("session storage is unavailable")))?;
+    storage.set_item(PENDING_KEY, &serde_json::to_string(&pending)?)?;
+    web_sys::window().unwrap().location().set_href(&url)?;
+    Ok(())
+}
+
+// checks the state against the one of start and takes the pending flow, a
+// second call with the same response fails
+pub fn finish(code: String, state: &str) -> BasicResult<(Intent, CodeReq)> {
+    let storage = session_storage()
+        .ok_or_else(|| ErrorKind::OtherError(String::from("session storage is unavailable")))?;
+    let pending = storage.get_item(PENDING_KEY)?;
+    storage.remove_item(PENDING_KEY)?;
+    let pending = match pending {
+        Some(v) => serde_json::from_str::<Pending>(&v)?,
+        None => return Err(ErrorKind::OtherError(i18n::t("login.sso_invalid"))),
+    };
+    if pending.state != state {
+        return Err(ErrorKind::OtherError(i18n::t("login.sso_invalid")));
+    }
+    Ok((
+        pending.intent,
+        CodeReq {
+            code,
+            code_verifier: pending.verifier,
+            redirect_uri: redirect_uri(),
+            nonce: pending.nonce,
+        },
+    ))
+}
+
+pub async fn sign_in(req: &CodeReq) -> BasicResult<SsoLoginRes> {
+    request::post::<SsoLoginRes, _>(Host::AxumBase, "/auth/sso", req)
+        .await?
+        .data
+        .ok_or_else(|| ErrorKind::OtherError(String::from("sso sign in returned no token")))
+}
+
+pub async fn link(req: &CodeReq) -> BasicResult<()> {
+    request::post::<(), _>(Host::AxumBase, "/user/sso", req).await?;
+    Ok(())
+}
+
+pub async fn identities() -> BasicResult<Vec<Identity>> {
+    let res =
+        request::get::<Vec<Identity>, Vec<(&str, &str)>, _>(Host::AxumBase, "/user/sso", None)
+            .await?;
+    Ok(res.data.unwrap_or_default())
+}
+
+pub async fn unlink(id: &str) -> BasicResult<()> {
+    request::delete::<(), _>(Host::AxumBase, &format!("/user/sso/{}", id), &()).await?;
+    Ok(())
+}
@@ -0,0 +1,42 @@
+use gloo::events::EventListener;
+use wasm_bindgen::JsCast;
+use web_sys::StorageEvent;
+
+// a change of localStorage made by another tab of the same origin
+#[derive(Clone, PartialEq, Debug)]
+pub struct StorageChange {
+    // None when the other tab cleared the whole storage
+    pub key: Option<String>,
+    // None when the key was removed
+    pub value: Option<String>,
+}
+
+impl StorageChange {
+    pub fn is(&self, key: &str) -> bool {
+        self.key.as_deref().map_or(true, |x| x == key)
+    }
+}
+
+// the browser fires "storage" in every tab but the one that wrote, so writes
+// made while handling a change do not bounce back; drop the listener to stop
+pub fn listen(f: impl Fn(StorageChange) + 'static) -> Option<EventListener> {
+    let window = web_sys::window()?;
+    Some(EventListener::new(&window, "storage", move |e| {
+        let e = match e.dyn_ref::<StorageEvent>() {
+            Some(v) => v,
+            None => return,
+        };
+        // sessionStorage is per tab and never shared
+        let local = e.storage_area().is_some_and(|x| {
+            web_sys::window()
+                .and_then(|w| w.local_storage().ok().flatten())
+                .is_some_and(|l| l == x)
+        });
+        if local {
+            f(StorageChange {
+                key: e.key(),
+                value: e.new_value(),
+            });
+        }
+    }))
+}
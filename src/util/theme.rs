@@ -0,0 +1,150 @@
+use crate::util::common;
+use crate::util::sync;
+use gloo::events::EventListener;
+use yew::prelude::*;
+
+pub const THEME_KEY: &str = "theme";
+const DARK_QUERY: &str = "(prefers-color-scheme: dark)";
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum ThemeMode {
+    Light,
+    Dark,
+    // follows the operating system
+    #[default]
+    System,
+}
+
+impl ThemeMode {
+    pub const ALL: [ThemeMode; 3] = [ThemeMode::Light, ThemeMode::Dark, ThemeMode::System];
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            ThemeMode::Light => "light",
+            ThemeMode::Dark => "dark",
+            ThemeMode::System => "system",
+        }
+    }
+
+    pub fn from_code(code: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|x| x.code() == code)
+    }
+
+    pub fn icon(&self) -> &'static str {
+        match self {
+            ThemeMode::Light => "fa-solid fa-sun",
+            ThemeMode::Dark => "fa-solid fa-moon",
+            ThemeMode::System => "fa-solid fa-circle-half-stroke",
+        }
+    }
+
+    // catalog key of the label
+    pub fn label_key(&self) -> &'static str {
+        match self {
+            ThemeMode::Light => "theme.light",
+            ThemeMode::Dark => "theme.dark",
+            ThemeMode::System => "theme.system",
+        }
+    }
+}
+
+pub fn current() -> ThemeMode {
+    common::get_local_storage(THEME_KEY)
+        .and_then(|x| ThemeMode::from_code(&x))
+        .unwrap_or_default()
+}
+
+fn system_dark() -> bool {
+    web_sys::window()
+        .and_then(|x| x.match_media(DARK_QUERY).ok().flatten())
+        .map(|x| x.matches())
+        .unwrap_or_default()
+}
+
+// "light" or "dark", what the stylesheets see as data-theme on <html>
+pub fn apply(mode: ThemeMode) {
+    let dark = match mode {
+        ThemeMode::Light => false,
+        ThemeMode::Dark => true,
+        ThemeMode::System => system_dark(),
+    };
+    if let Some(el) = web_sys::window()
+        .and_then(|x| x.document())
+        .and_then(|x| x.document_element())
+    {
+        el.set_attribute("data-theme", if dark { "dark" } else { "light" })
+            .unwrap_or_default();
+    }
+}
+
+pub fn set_mode(mode: ThemeMode) {
+    common::set_local_storage(THEME_KEY, mode.code());
+    apply(mode);
+}
+
+#[derive(Clone, PartialEq)]
+pub struct Theme {
+    pub mode: ThemeMode,
+    set: Callback<ThemeMode>,
+}
+
+impl Theme {
+    pub fn set_mode(&self, mode: ThemeMode) {
+        self.set.emit(mode);
+    }
+}
+
+#[hook]
+pub fn use_theme() -> Theme {
+    use_context::<Theme>().expect("use_theme is called outside of ThemeProvider")
+}
+
+#[derive(PartialEq, Properties)]
+pub struct ThemeProviderProps {
+    #[prop_or_default]
+    pub children: Html,
+}
+
+#[function_component(ThemeProvider)]
+pub fn theme_provider(props: &ThemeProviderProps) -> Html {
+    let mode = use_state(current);
+
+    // in system mode the page follows changes of the os setting
+    use_effect_with(*mode, |mode| {
+        apply(*mode);
+        let listener = match mode {
+            ThemeMode::System => web_sys::window()
+                .and_then(|x| x.match_media(DARK_QUERY).ok().flatten())
+                .map(|x| EventListener::new(&x, "change", |_| apply(ThemeMode::System))),
+            _ => None,
+        };
+        move || drop(listener)
+    });
+
+    // switched in another tab
+    {
+        let mode = mode.clone();
+        use_effect_with((), move |_| {
+            let listener = sync::listen(move |change| {
+                if change.is(THEME_KEY) {
+                    mode.set(current());
+                }
+            });
+            move || drop(listener)
+        });
+    }
+
+    let theme = Theme {
+        mode: *mode,
+        set: Callback::from(move |x| {
+            set_mode(x);
+            mode.set(x);
+        }),
+    };
+
+    html! {
+        <ContextProvider<Theme> context={theme}>
+            { props.children.clone() }
+        </ContextProvider<Theme>>
+    }
+}
@@ -0,0 +1,66 @@
+use crate::util::i18n;
+use chrono::{DateTime, Local, NaiveDateTime, TimeZone, Utc};
+
+// older timestamps are shown as a date instead of "n days ago"
+const RELATIVE_DAYS: i64 = 30;
+// epoch values above this are milliseconds, below it seconds (year 5138 in seconds)
+const MILLIS_THRESHOLD: i64 = 100_000_000_000;
+
+// the api returns epoch seconds, values taken in the browser are milliseconds,
+// both are accepted
+pub fn from_epoch(value: i64) -> Option<DateTime<Utc>> {
+    if value.abs() >= MILLIS_THRESHOLD {
+        Utc.timestamp_millis_opt(value).single()
+    } else {
+        Utc.timestamp_opt(value, 0).single()
+    }
+}
+
+// chat messages carry rfc3339 or naive local times
+pub fn parse(time: &str) -> Option<DateTime<Local>> {
+    if let Ok(v) = DateTime::parse_from_rfc3339(time) {
+        return Some(v.with_timezone(&Local));
+    }
+    ["%Y-%m-%d %H:%M:%S%.f", "%Y-%m-%dT%H:%M:%S%.f"]
+        .iter()
+        .find_map(|f| NaiveDateTime::parse_from_str(time, f).ok())
+        .and_then(|x| Local.from_local_datetime(&x).single())
+}
+
+// in the viewer's timezone and locale
+pub fn format_local<Tz: TimeZone>(time: &DateTime<Tz>) -> String {
+    i18n::format_datetime(&time.with_timezone(&Local))
+}
+
+// today only the time is shown
+pub fn format_short<Tz: TimeZone>(time: &DateTime<Tz>) -> String {
+    let time = time.with_timezone(&Local);
+    if time.date_naive() == Local::now().date_naive() {
+        i18n::format_time(&time)
+    } else {
+        i18n::format_datetime(&time)
+    }
+}
+
+// for tooltips
+pub fn format_utc<Tz: TimeZone>(time: &DateTime<Tz>) -> String {
+    time.with_timezone(&Utc)
+        .format("%Y-%m-%d %H:%M:%S UTC")
+        .to_string()
+}
+
+pub fn format_relative<Tz: TimeZone>(time: &DateTime<Tz>) -> String {
+    let elapsed = Utc::now().signed_duration_since(time.with_timezone(&Utc));
+    // clocks of server and browser may differ a little
+    if elapsed.num_minutes() < 1 {
+        i18n::t("time.just_now")
+    } else if elapsed.num_hours() < 1 {
+        i18n::t_plural("time.minutes_ago", elapsed.num_minutes())
+    } else if elapsed.num_days() < 1 {
+        i18n::t_plural("time.hours_ago", elapsed.num_hours())
+    } else if elapsed.num_days() < RELATIVE_DAYS {
+        i18n::t_plural("time.days_ago", elapsed.num_days())
+    } else {
+        format_local(time)
+    }
+}
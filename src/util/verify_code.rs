@@ -0,0 +1,310 @@
+use crate::util::challenge::{ChallengeField, ChallengePurpose, Pass};
+use crate::util::common;
+use crate::util::error::ErrorKind;
+use crate::util::i18n::use_translation;
+use crate::util::request::{self, Host};
+use evolve_axum_cli::models::{SendEmailCodeFrom, SendEmailCodeReq};
+use gloo::timers::callback::Timeout;
+use serde::{Deserialize, Serialize};
+use wasm_bindgen_futures::spawn_local;
+use yew::prelude::*;
+
+// a sent code is accepted this long, the code input stays enabled meanwhile
+const CODE_TTL_MILLIS: i64 = 10 * 60 * 1000;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CodePurpose {
+    Register,
+    ResetPwd,
+    // needs the token of the signed in user
+    ChangeEmail,
+}
+
+impl CodePurpose {
+    // one cooldown per purpose, switching the email does not skip it
+    fn storage_key(&self) -> &'static str {
+        match self {
+            CodePurpose::Register => "code_cooldown_register",
+            CodePurpose::ResetPwd => "code_cooldown_reset_pwd",
+            CodePurpose::ChangeEmail => "code_cooldown_change_email",
+        }
+    }
+
+    fn send_from(&self) -> SendEmailCodeFrom {
+        match self {
+            CodePurpose::Register => SendEmailCodeFrom::Register,
+            CodePurpose::ResetPwd => SendEmailCodeFrom::ChangePwd,
+            CodePurpose::ChangeEmail => SendEmailCodeFrom::ChangeEmail,
+        }
+    }
+}
+
+// kept in localStorage so that reloading or leaving the page keeps the cooldown
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+struct Cooldown {
+    email: String,
+    sent_at: i64,
+    // epoch millis, another code can be sent after it
+    until: i64,
+    // sends left, None when the server did not tell
+    remaining: Option<i64>,
+}
+
+fn now() -> i64 {
+    chrono::Utc::now().timestamp_millis()
+}
+
+fn load(purpose: CodePurpose) -> Option<Cooldown> {
+    common::get_local_storage(purpose.storage_key())
+        .and_then(|x| serde_json::from_str::<Cooldown>(&x).ok())
+        .filter(|x| x.sent_at + CODE_TTL_MILLIS > now() || x.until > now())
+}
+
+fn save(purpose: CodePurpose, cooldown: Option<&Cooldown>) {
+    match cooldown.and_then(|x| serde_json::to_string(x).ok()) {
+        Some(v) => common::set_local_storage(purpose.storage_key(), &v),
+        None => common::del_local_storage(purpose.storage_key()),
+    }
+}
+
+// the generated request with the pass of the bot challenge
+#[derive(Serialize)]
+struct SendCodeReq {
+    #[serde(flatten)]
+    req: SendEmailCodeReq,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    challenge: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct SendCodeRes {
+    // seconds until another code can be sent
+    cooldown: i64,
+    // sends left for the day, None when the server does not limit them
+    #[serde(default)]
+    remaining: Option<i64>,
+}
+
+enum SendError {
+    // e.g. a code was sent a moment ago, the one sent before can be used
+    Hint(String),
+    Failed(String),
+}
+
+async fn send_code(
+    purpose: CodePurpose,
+    email: String,
+    challenge: Option<String>,
+) -> Result<(i64, Option<i64>), SendError> {
+    let req = SendCodeReq {
+        req: SendEmailCodeReq {
+            email,
+            from: purpose.send_from(),
+        },
+        challenge,
+    };
+    // the token is sent when there is one, changing the email needs it
+    match request::post::<SendCodeRes, _>(Host::AxumBase, "/user/send_email_code", &req).await {
+        Ok(res) => match res.data {
+            Some(v) => Ok((v.cooldown, v.remaining)),
+            None => Ok((0, None)),
+        },
+        Err(ErrorKind::Hint(msg)) => Err(SendError::Hint(msg)),
+        Err(err) => Err(SendError::Failed(err.to_string())),
+    }
+}
+
+// the state of sending a verification code to one email, shared by the code
+// button, the code input and the status line
+#[derive(Clone, PartialEq)]
+pub struct CodeSender {
+    // a code was sent to the email and can be entered
+    pub sent: bool,
+    pub sending: bool,
+    // seconds until another code can be sent
+    pub countdown: i64,
+    pub remaining: Option<i64>,
+    pub error: Option<String>,
+    // the bot challenge of the next send was passed
+    pub passed: bool,
+    // remounts the challenge, a pass is used by one send
+    challenge_round: u32,
+    on_pass: Callback<Option<Pass>>,
+    send: Callback<()>,
+    reset: Callback<()>,
+}
+
+impl CodeSender {
+    pub fn can_send(&self) -> bool {
+        self.passed && !self.sending && self.countdown == 0 && self.remaining != Some(0)
+    }
+
+    pub fn send(&self) {
+        self.send.emit(());
+    }
+
+    // the code was used, the cooldown is left to the server
+    pub fn reset(&self) {
+        self.reset.emit(());
+    }
+}
+
+#[hook]
+pub fn use_code_sender(purpose: CodePurpose, email: &str) -> CodeSender {
+    let cooldown = use_state(move || load(purpose));
+    let sending = use_state(|| false);
+    let error = use_state(|| None::<String>);
+    let pass = use_state(|| None::<Pass>);
+    let challenge_round = use_state(|| 0u32);
+    let force_update = use_force_update();
+
+    let countdown = cooldown
+        .as_ref()
+        .map(|x| ((x.until - now()).max(0) + 999) / 1000)
+        .unwrap_or_default();
+    // ticks once a second while counting down
+    use_effect_with(countdown, move |secs| {
+        let timeout = (*secs > 0).then(|| Timeout::new(1000, move || force_update.force_update()));
+        move || drop(timeout)
+    });
+
+    let send = {
+        let email = email.to_string();
+        let cooldown = cooldown.clone();
+        let sending = sending.clone();
+        let error = error.clone();
+        let pass = pass.clone();
+        let challenge_round = challenge_round.clone();
+        Callback::from(move |_| {
+            let challenge = match (*sending, &*pass) {
+                (false, Some(v)) => v.token(),
+                _ => return,
+            };
+            sending.set(true);
+            pass.set(None);
+            challenge_round.set(*challenge_round + 1);
+            let email = email.clone();
+            let cooldown = cooldown.clone();
+            let sending = sending.clone();
+            let error = error.clone();
+            spawn_local(async move {
+                let sent = |secs: i64, remaining: Option<i64>| Cooldown {
+                    email: email.clone(),
+                    sent_at: now(),
+                    until: now() + secs.max(0) * 1000,
+                    remaining,
+                };
+                let res = match send_code(purpose, email.clone(), challenge).await {
+                    Ok((secs, remaining)) => {
+                        error.set(None);
+                        Some(sent(secs, remaining))
+                    }
+                    Err(SendError::Hint(msg)) => {
+                        error.set(Some(msg));
+                        let prev = (*cooldown).clone().filter(|x| x.email == email);
+                        Some(prev.unwrap_or_else(|| sent(0, None)))
+                    }
+                    Err(SendError::Failed(msg)) => {
+                        error.set(Some(msg));
+                        None
+                    }
+                };
+                if let Some(v) = res {
+                    save(purpose, Some(&v));
+                    cooldown.set(Some(v));
+                }
+                sending.set(false);
+            });
+        })
+    };
+
+    let reset = {
+        let cooldown = cooldown.clone();
+        let error = error.clone();
+        Callback::from(move |_| {
+            let kept = (*cooldown).clone().map(|x| Cooldown { sent_at: 0, ..x });
+            save(purpose, kept.as_ref());
+            cooldown.set(kept);
+            error.set(None);
+        })
+    };
+
+    CodeSender {
+        sent: cooldown
+            .as_ref()
+            .is_some_and(|x| x.email == email && x.sent_at + CODE_TTL_MILLIS > now()),
+        sending: *sending,
+        countdown,
+        remaining: cooldown.as_ref().and_then(|x| x.remaining),
+        error: (*error).clone(),
+        passed: pass.is_some(),
+        challenge_round: *challenge_round,
+        on_pass: Callback::from(move |x| pass.set(x)),
+        send,
+        reset,
+    }
+}
+
+#[derive(PartialEq, Properties)]
+pub struct CodeButtonProps {
+    pub sender: CodeSender,
+    // e.g. the email is not valid yet
+    #[prop_or_default]
+    pub disabled: bool,
+    #[prop_or_default]
+    pub class: Classes,
+}
+
+// shows the seconds left instead of the label while cooling down
+#[function_component(CodeButton)]
+pub fn code_button(props: &CodeButtonProps) -> Html {
+    let i18n = use_translation();
+    let sender = props.sender.clone();
+    let text = match sender.countdown {
+        0 => i18n.t("register.generate_code"),
+        secs => secs.to_string(),
+    };
+    let onclick = {
+        let sender = sender.clone();
+        Callback::from(move |_: MouseEvent| sender.send())
+    };
+    html! {
+        <button class={classes!("button", props.class.clone(), sender.sending.then_some("is-loading"))}
+            disabled={props.disabled || !sender.can_send()} {onclick}>
+            {text}
+        </button>
+    }
+}
+
+#[derive(PartialEq, Properties)]
+pub struct CodeStatusProps {
+    pub sender: CodeSender,
+}
+
+// the error of the last send, or how many sends are left
+#[function_component(CodeStatus)]
+pub fn code_status(props: &CodeStatusProps) -> Html {
+    let i18n = use_translation();
+    match (&props.sender.error, props.sender.remaining) {
+        (Some(msg), _) => html! { <p class="help is-danger">{msg.clone()}</p> },
+        (None, Some(0)) => html! { <p class="help is-danger">{i18n.t("code.no_more")}</p> },
+        (None, Some(count)) => html! {
+            <p class="help">{i18n.t_plural("code.remaining", count)}</p>
+        },
+        (None, None) => html! {},
+    }
+}
+
+#[derive(PartialEq, Properties)]
+pub struct CodeChallengeProps {
+    pub sender: CodeSender,
+}
+
+// the bot challenge every send has to pass first
+#[function_component(CodeChallenge)]
+pub fn code_challenge(props: &CodeChallengeProps) -> Html {
+    html! {
+        <ChallengeField key={props.sender.challenge_round} purpose={ChallengePurpose::SendCode}
+            onpass={props.sender.on_pass.clone()}/>
+    }
+}